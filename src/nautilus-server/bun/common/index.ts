@@ -8,11 +8,62 @@
 
 export {
     type NautilusKeypair,
+    type AttestationDocument,
+    type BundledKey,
+    type KeyBundle,
+    type PcrDescription,
+    type NsmDescription,
+    type Diagnostics,
+    type RegisterEnclaveTxParams,
+    type KeyRotation,
+    type MultiSigCommittee,
+    type MultiSigPublicKey,
+    type PartialSignature,
+    NautilusError,
+    NautilusErrorCode,
+    KeyScheme,
+    MessageHash,
+    PublicKeyEncoding,
+    RngPolicy,
+    EntropySource,
+    setRngPolicy,
+    lastEntropySource,
     generateKeypair,
+    generateNamedKeypair,
+    getKeypairByName,
+    rotateKeypair,
     getPublicKeyHex,
+    getPublicKey,
+    getPublicKeyBytes,
+    getSuiAddress,
+    getSuiPublicKey,
     getAttestation,
+    getAttestationWithData,
+    getAttestationBytes,
+    getCachedAttestation,
+    refreshAttestation,
+    setAttestationCacheTtl,
+    parseAttestation,
+    attestKeyBundle,
+    parseKeyBundle,
+    describePcr,
+    extendPcr,
+    lockPcr,
+    describeNsm,
+    getDiagnostics,
     signIntentMessage,
+    signIntentMessageEcdsa,
+    verifySignature,
+    aggregateBlsSignatures,
+    verifyBlsAggregate,
+    buildMultiSigPublicKey,
+    combineMultiSigSignatures,
+    verifyMultiSig,
+    signIntentMessageBytes,
+    buildRegisterEnclaveTx,
+    closeNsm,
     freeKeypair,
+    destroyAllKeys,
     freeCString
 } from './nautilus'
 
@@ -8,7 +8,7 @@
  * It handles keypair generation, attestation, and message signing.
  */
 
-import { dlopen, FFIType } from 'bun:ffi'
+import { type CString, dlopen, FFIType, read, toArrayBuffer } from 'bun:ffi'
 
 /**
  * Resolves the path to the Nautilus shared library
@@ -38,27 +38,303 @@ async function resolveLibPath(): Promise<string> {
  */
 const libPath = await resolveLibPath()
 const lib = dlopen(libPath, {
-    nautilus_generate_ed25519_keypair: { returns: FFIType.ptr, args: [] },
-    nautilus_free_keypair: { returns: FFIType.void, args: [FFIType.ptr] },
-    nautilus_get_public_key_hex: { returns: FFIType.cstring, args: [FFIType.ptr] },
-    nautilus_get_attestation: { returns: FFIType.cstring, args: [FFIType.ptr] },
-    nautilus_free_cstr: { returns: FFIType.void, args: [FFIType.cstring] },
+    nautilus_generate_ed25519_keypair: { returns: FFIType.u64_fast, args: [] },
+    nautilus_generate_keypair: { returns: FFIType.u64_fast, args: [FFIType.u8] },
+    nautilus_generate_named_keypair: { returns: FFIType.u64_fast, args: [FFIType.u8, FFIType.ptr, FFIType.usize] },
+    nautilus_get_keypair_by_name: { returns: FFIType.u64_fast, args: [FFIType.ptr, FFIType.usize] },
+    nautilus_free_keypair: { returns: FFIType.i32, args: [FFIType.u64] },
+    nautilus_rotate_keypair: {
+        returns: FFIType.cstring,
+        args: [FFIType.u64, FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_destroy_all_keys: { returns: FFIType.i32, args: [] },
+    nautilus_set_rng_policy: { returns: FFIType.i32, args: [FFIType.u8] },
+    nautilus_last_entropy_source: { returns: FFIType.u8, args: [] },
+    nautilus_get_public_key_hex: { returns: FFIType.cstring, args: [FFIType.u64] },
+    nautilus_get_public_key: { returns: FFIType.cstring, args: [FFIType.u64, FFIType.u8] },
+    nautilus_get_public_key_bytes: { returns: FFIType.ptr, args: [FFIType.u64] },
+    nautilus_get_sui_address: { returns: FFIType.cstring, args: [FFIType.u64] },
+    nautilus_get_sui_public_key: { returns: FFIType.cstring, args: [FFIType.u64] },
+    nautilus_get_attestation: { returns: FFIType.cstring, args: [FFIType.u64] },
+    nautilus_get_attestation_with_data: {
+        returns: FFIType.cstring,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_get_attestation_bytes: {
+        returns: FFIType.ptr,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_get_cached_attestation: {
+        returns: FFIType.cstring,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_refresh_attestation: { returns: FFIType.cstring, args: [FFIType.u64, FFIType.ptr, FFIType.usize] },
+    nautilus_set_attestation_cache_ttl_ms: { returns: FFIType.i32, args: [FFIType.u64] },
+    nautilus_attest_key_bundle: {
+        returns: FFIType.cstring,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_parse_key_bundle: {
+        returns: FFIType.cstring,
+        args: [FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_parse_attestation: { returns: FFIType.cstring, args: [FFIType.ptr, FFIType.usize] },
+    nautilus_describe_pcr: { returns: FFIType.cstring, args: [FFIType.u16] },
+    nautilus_extend_pcr: { returns: FFIType.cstring, args: [FFIType.u16, FFIType.ptr, FFIType.usize] },
+    nautilus_lock_pcr: { returns: FFIType.i32, args: [FFIType.u16] },
+    nautilus_verify_signature: {
+        returns: FFIType.i32,
+        args: [FFIType.u8, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_aggregate_bls_signatures: { returns: FFIType.ptr, args: [FFIType.ptr, FFIType.usize] },
+    nautilus_verify_bls_aggregate: {
+        returns: FFIType.i32,
+        args: [FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_build_multisig_public_key: { returns: FFIType.cstring, args: [FFIType.ptr, FFIType.usize] },
+    nautilus_combine_multisig_signatures: {
+        returns: FFIType.ptr,
+        args: [FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_verify_multisig: {
+        returns: FFIType.i32,
+        args: [FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize, FFIType.ptr, FFIType.usize]
+    },
+    nautilus_build_register_enclave_tx: { returns: FFIType.ptr, args: [FFIType.ptr, FFIType.usize] },
+    nautilus_describe_nsm: { returns: FFIType.cstring, args: [] },
+    nautilus_diagnostics: { returns: FFIType.cstring, args: [] },
+    nautilus_nsm_close: { returns: FFIType.void, args: [] },
+    nautilus_free_cstr: { returns: FFIType.void, args: [FFIType.ptr] },
+    nautilus_free_buffer: { returns: FFIType.void, args: [FFIType.ptr] },
+    nautilus_last_error_code: { returns: FFIType.i32, args: [] },
+    nautilus_last_error_message: { returns: FFIType.cstring, args: [] },
     nautilus_sign_intent_message_json: {
         returns: FFIType.cstring,
-        args: [FFIType.ptr, FFIType.ptr, FFIType.usize, FFIType.u64, FFIType.u8]
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.u64, FFIType.u8]
+    },
+    nautilus_sign_intent_message_ecdsa: {
+        returns: FFIType.cstring,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.u64, FFIType.u8, FFIType.u8, FFIType.bool]
+    },
+    nautilus_sign_intent_message_bytes: {
+        returns: FFIType.ptr,
+        args: [FFIType.u64, FFIType.ptr, FFIType.usize, FFIType.u64, FFIType.u8, FFIType.ptr]
     }
 })
 
 /**
- * Nautilus Keypair - opaque pointer to Rust keypair
+ * Status codes reported by `nautilus_last_error_code`
+ */
+export const NautilusErrorCode = {
+    Ok: 0,
+    NsmUnavailable: 1,
+    NsmRequestFailed: 2,
+    InvalidPointer: 3,
+    Serialization: 4,
+    UnknownIntent: 5,
+    Panic: 6,
+    InvalidAttestation: 7,
+    InvalidArgument: 8,
+    InvalidSignature: 9,
+    InvalidHandle: 10
+} as const
+
+/**
+ * Error raised when a Nautilus FFI call fails
+ */
+export class NautilusError extends Error {
+    readonly code: number
+
+    constructor(code: number, message: string) {
+        super(message)
+        this.name = 'NautilusError'
+        this.code = code
+    }
+}
+
+/**
+ * Build a NautilusError from the last error recorded on this thread
+ */
+function lastError(): NautilusError {
+    const code = lib.symbols.nautilus_last_error_code()
+    const message = lib.symbols.nautilus_last_error_message()
+    if (message === null) return new NautilusError(code, `nautilus error ${code}`)
+    try {
+        return new NautilusError(code, String(message))
+    } finally {
+        lib.symbols.nautilus_free_cstr(message.ptr)
+    }
+}
+
+/**
+ * Throw the recorded error unless the returned status code is Ok
+ */
+function checkStatus(code: number): void {
+    if (code !== NautilusErrorCode.Ok) throw lastError()
+}
+
+/**
+ * Copy a returned C string into a JavaScript string and free it,
+ * throwing the recorded error on NULL
+ */
+function takeString(cstr: any): string {
+    if (cstr === null) throw lastError()
+    try {
+        return String(cstr)
+    } finally {
+        lib.symbols.nautilus_free_cstr(cstr.ptr)
+    }
+}
+
+/**
+ * Copy a returned FfiBuffer ({ ptr, len, cap }) into a Buffer and free it,
+ * throwing the recorded error on NULL
+ */
+function takeBuffer(buf: any): Buffer {
+    if (buf === null || buf === 0) throw lastError()
+    try {
+        const len = Number(read.u64(buf, 8))
+        if (len === 0) return Buffer.alloc(0)
+        return Buffer.from(new Uint8Array(toArrayBuffer(read.ptr(buf, 0), 0, len)))
+    } finally {
+        lib.symbols.nautilus_free_buffer(buf)
+    }
+}
+
+/**
+ * Nautilus Keypair - opaque handle into the Rust key registry (never 0)
  */
 export type NautilusKeypair = number
 
 /**
- * Generate a new Ed25519 keypair
+ * Keypair signature scheme (Sui flag byte)
  */
-export function generateKeypair(): NautilusKeypair {
-    return lib.symbols.nautilus_generate_ed25519_keypair() as NautilusKeypair
+export const KeyScheme = {
+    Ed25519: 0,
+    Secp256k1: 1,
+    Secp256r1: 2,
+    /** BLS12-381 min-sig: 48-byte signatures that aggregate across replicas */
+    Bls12381: 4
+} as const
+
+/**
+ * Textual public key encoding for getPublicKey
+ */
+export const PublicKeyEncoding = {
+    /** Lowercase hex, as getPublicKeyHex */
+    Hex: 0,
+    /** 0x-prefixed hex */
+    PrefixedHex: 1,
+    Base64: 2,
+    /** Base64 of the Sui flagged key (scheme flag || public key), as getSuiPublicKey */
+    SuiBase64: 3,
+    /** Multibase base58btc of the multicodec key (z...) */
+    Multibase: 4,
+    /** did:key:z... */
+    DidKey: 5
+} as const
+
+/**
+ * Hash applied before ECDSA signing
+ */
+export const MessageHash = {
+    /** SHA-256 (Sui ecdsa_k1) */
+    Sha256: 0,
+    /** Keccak-256 (EVM ecrecover) */
+    Keccak256: 1
+} as const
+
+/**
+ * Entropy policy for key generation
+ */
+export const RngPolicy = {
+    /** NSM entropy mixed with the OS RNG; fail without NSM (default) */
+    NsmMixed: 0,
+    /** NSM entropy only; fail without NSM */
+    NsmOnly: 1,
+    /** NSM entropy mixed with the OS RNG; fall back to the OS RNG without NSM */
+    NsmMixedOrOs: 2,
+    /** OS RNG only (development outside an enclave) */
+    Os: 3
+} as const
+
+/**
+ * Entropy source used for the most recently generated key
+ */
+export const EntropySource = {
+    None: 0,
+    NsmMixed: 1,
+    Nsm: 2,
+    OsFallback: 3,
+    Os: 4
+} as const
+
+/**
+ * Set the entropy policy used by generateKeypair
+ */
+export function setRngPolicy(policy: number): void {
+    checkStatus(lib.symbols.nautilus_set_rng_policy(policy))
+}
+
+/**
+ * Entropy source used for the most recently generated key
+ */
+export function lastEntropySource(): number {
+    return lib.symbols.nautilus_last_entropy_source()
+}
+
+/**
+ * Generate a new keypair (Ed25519 by default)
+ *
+ * @param scheme - Signature scheme, see KeyScheme
+ * @throws NautilusError if the NSM is unavailable under a strict RNG policy
+ */
+export function generateKeypair(scheme: number = KeyScheme.Ed25519): NautilusKeypair {
+    return checkKeypair(lib.symbols.nautilus_generate_keypair(scheme))
+}
+
+/**
+ * Generate a new keypair registered under a name, for later lookup with getKeypairByName
+ *
+ * @param name - Unique key name, e.g. 'signing' or 'session'
+ * @param scheme - Signature scheme, see KeyScheme
+ * @throws NautilusError if the name is empty or already in use
+ */
+export function generateNamedKeypair(
+    name: string,
+    scheme: number = KeyScheme.Ed25519
+): NautilusKeypair {
+    const nameBytes = Buffer.from(name, 'utf8')
+    return checkKeypair(
+        lib.symbols.nautilus_generate_named_keypair(
+            scheme,
+            nameBytes.byteLength > 0 ? nameBytes : null,
+            nameBytes.byteLength
+        )
+    )
+}
+
+function checkKeypair(keypair: number): NautilusKeypair {
+    if (keypair === 0) throw lastError()
+    if (lastEntropySource() === EntropySource.OsFallback) {
+        console.warn('[nautilus] NSM unavailable: keypair generated from OS RNG only')
+    }
+    return keypair
+}
+
+/**
+ * Get the handle of the keypair registered under a name
+ *
+ * @throws NautilusError if no live keypair has this name
+ */
+export function getKeypairByName(name: string): NautilusKeypair {
+    const nameBytes = Buffer.from(name, 'utf8')
+    const keypair = lib.symbols.nautilus_get_keypair_by_name(
+        nameBytes.byteLength > 0 ? nameBytes : null,
+        nameBytes.byteLength
+    )
+    if (keypair === 0) throw lastError()
+    return keypair
 }
 
 /**
@@ -66,19 +342,297 @@ export function generateKeypair(): NautilusKeypair {
  */
 export function getPublicKeyHex(keypair: NautilusKeypair): string {
     const cstr = lib.symbols.nautilus_get_public_key_hex(keypair)
-    // Convert FFI cstring to JavaScript string
-    const result = String(cstr)
-    return result
+    return takeString(cstr)
+}
+
+/**
+ * Get the public key in the chosen encoding
+ *
+ * @param encoding - See PublicKeyEncoding
+ */
+export function getPublicKey(keypair: NautilusKeypair, encoding: number): string {
+    return takeString(lib.symbols.nautilus_get_public_key(keypair, encoding))
+}
+
+/**
+ * Get the raw public key bytes (32 bytes for Ed25519, 33 for secp256k1 and secp256r1, 96 for BLS12-381)
+ */
+export function getPublicKeyBytes(keypair: NautilusKeypair): Buffer {
+    return takeBuffer(lib.symbols.nautilus_get_public_key_bytes(keypair))
+}
+
+/**
+ * Get the Sui address owned by the keypair (0x-prefixed hex)
+ *
+ * @throws NautilusError for BLS12-381 keypairs, which cannot own Sui accounts
+ */
+export function getSuiAddress(keypair: NautilusKeypair): string {
+    return takeString(lib.symbols.nautilus_get_sui_address(keypair))
+}
+
+/**
+ * Get the Sui flagged public key (scheme flag byte || public key) in base64
+ */
+export function getSuiPublicKey(keypair: NautilusKeypair): string {
+    return takeString(lib.symbols.nautilus_get_sui_public_key(keypair))
 }
 
 /**
  * Get the Nitro attestation document
+ *
+ * @throws NautilusError, e.g. `NsmUnavailable` outside a Nitro Enclave
  */
 export function getAttestation(keypair: NautilusKeypair): string {
     const cstr = lib.symbols.nautilus_get_attestation(keypair)
-    // Convert FFI cstring to JavaScript string
-    const result = String(cstr)
-    return result
+    return takeString(cstr)
+}
+
+/**
+ * Get the Nitro attestation document bound to a caller-supplied nonce and user data
+ *
+ * @param keypair - The keypair whose public key is committed in the document
+ * @param nonce - Optional nonce for challenge-response freshness (max 512 bytes)
+ * @param userData - Optional application data, e.g. config hash (max 512 bytes)
+ * @returns Hex-encoded attestation document
+ * @throws NautilusError, e.g. `NsmUnavailable` outside a Nitro Enclave
+ */
+export function getAttestationWithData(
+    keypair: NautilusKeypair,
+    nonce?: Buffer,
+    userData?: Buffer
+): string {
+    const cstr = lib.symbols.nautilus_get_attestation_with_data(
+        keypair,
+        nonce && nonce.byteLength > 0 ? nonce : null,
+        nonce ? nonce.byteLength : 0,
+        userData && userData.byteLength > 0 ? userData : null,
+        userData ? userData.byteLength : 0
+    )
+    return takeString(cstr)
+}
+
+/**
+ * Same as getAttestationWithData, but returns the raw (binary) attestation document
+ */
+export function getAttestationBytes(
+    keypair: NautilusKeypair,
+    nonce?: Buffer,
+    userData?: Buffer
+): Buffer {
+    const buf = lib.symbols.nautilus_get_attestation_bytes(
+        keypair,
+        nonce && nonce.byteLength > 0 ? nonce : null,
+        nonce ? nonce.byteLength : 0,
+        userData && userData.byteLength > 0 ? userData : null,
+        userData ? userData.byteLength : 0
+    )
+    return takeBuffer(buf)
+}
+
+/**
+ * Get an attestation document, reusing the last one for this keypair while it is
+ * younger than the cache TTL and was requested with the same user data.
+ * A nonce always bypasses the cache.
+ *
+ * @param keypair - The keypair whose public key is committed in the document
+ * @param nonce - Optional nonce; forces a fresh document
+ * @param userData - Optional application data (max 512 bytes)
+ * @returns Hex-encoded attestation document
+ */
+export function getCachedAttestation(
+    keypair: NautilusKeypair,
+    nonce?: Buffer,
+    userData?: Buffer
+): string {
+    const cstr = lib.symbols.nautilus_get_cached_attestation(
+        keypair,
+        nonce && nonce.byteLength > 0 ? nonce : null,
+        nonce ? nonce.byteLength : 0,
+        userData && userData.byteLength > 0 ? userData : null,
+        userData ? userData.byteLength : 0
+    )
+    return takeString(cstr)
+}
+
+/**
+ * Request a new attestation document now and replace the cached one
+ */
+export function refreshAttestation(keypair: NautilusKeypair, userData?: Buffer): string {
+    const cstr = lib.symbols.nautilus_refresh_attestation(
+        keypair,
+        userData && userData.byteLength > 0 ? userData : null,
+        userData ? userData.byteLength : 0
+    )
+    return takeString(cstr)
+}
+
+/**
+ * Set how long cached attestation documents are reused (default 60 s; 0 disables caching)
+ */
+export function setAttestationCacheTtl(ttlMs: number): void {
+    checkStatus(lib.symbols.nautilus_set_attestation_cache_ttl_ms(BigInt(ttlMs)))
+}
+
+/**
+ * Decoded attestation document; byte fields are hex-encoded
+ */
+export interface AttestationDocument {
+    module_id: string
+    digest: string
+    timestamp: number
+    pcrs: Record<string, string>
+    certificate: string
+    cabundle: string[]
+    public_key: string | null
+    user_data: string | null
+    nonce: string | null
+}
+
+/**
+ * Decode a hex-encoded attestation document (as returned by getAttestation)
+ *
+ * This only decodes the document; it does not verify it.
+ *
+ * @throws NautilusError with `InvalidAttestation` if the document is malformed
+ */
+export function parseAttestation(attestationHex: string): AttestationDocument {
+    const doc = Buffer.from(attestationHex, 'hex')
+    const cstr = lib.symbols.nautilus_parse_attestation(doc, doc.byteLength)
+    return JSON.parse(takeString(cstr)) as AttestationDocument
+}
+
+/**
+ * Public key committed in a key bundle; public_key is hex-encoded
+ */
+export interface BundledKey {
+    purpose: 'signing' | 'encryption' | 'session'
+    public_key: string
+}
+
+/**
+ * Decoded key bundle; digest is the hex Blake2b-256 hash found in the document's user_data
+ */
+export interface KeyBundle {
+    version: number
+    keys: BundledKey[]
+    digest: string
+}
+
+/**
+ * Attest the keypair's signing key together with additional public keys
+ *
+ * @param keypair - The keypair whose public key is the document's public_key
+ * @param keys - Additional keys (purpose `encryption` or `session`, hex public key)
+ * @param nonce - Optional nonce for challenge-response freshness
+ * @returns Hex attestation document and hex BCS key bundle; send both to clients
+ */
+export function attestKeyBundle(
+    keypair: NautilusKeypair,
+    keys: BundledKey[],
+    nonce?: Buffer
+): { attestation: string; bundle: string } {
+    const json = Buffer.from(JSON.stringify(keys))
+    const cstr = lib.symbols.nautilus_attest_key_bundle(
+        keypair,
+        json,
+        json.byteLength,
+        nonce && nonce.byteLength > 0 ? nonce : null,
+        nonce ? nonce.byteLength : 0
+    )
+    return JSON.parse(takeString(cstr))
+}
+
+/**
+ * Decode a hex BCS key bundle, optionally checking that a hex attestation document
+ * commits to it (the document itself is not verified)
+ *
+ * @throws NautilusError with `InvalidAttestation` if the document does not commit to the bundle
+ */
+export function parseKeyBundle(bundleHex: string, attestationHex?: string): KeyBundle {
+    const bundle = Buffer.from(bundleHex, 'hex')
+    const doc = attestationHex ? Buffer.from(attestationHex, 'hex') : null
+    const cstr = lib.symbols.nautilus_parse_key_bundle(
+        bundle,
+        bundle.byteLength,
+        doc && doc.byteLength > 0 ? doc : null,
+        doc ? doc.byteLength : 0
+    )
+    return JSON.parse(takeString(cstr)) as KeyBundle
+}
+
+/**
+ * PCR state as reported by the NSM; value is hex-encoded
+ */
+export interface PcrDescription {
+    index: number
+    locked: boolean
+    value: string
+}
+
+/**
+ * Read the value and lock state of a PCR
+ */
+export function describePcr(index: number): PcrDescription {
+    const cstr = lib.symbols.nautilus_describe_pcr(index)
+    return JSON.parse(takeString(cstr)) as PcrDescription
+}
+
+/**
+ * Extend a PCR with data (PCR = SHA384(PCR || data)); use index 16 or above
+ *
+ * @returns New PCR value as hex
+ * @throws NautilusError if the PCR is locked or the index is invalid
+ */
+export function extendPcr(index: number, data: Buffer): string {
+    const cstr = lib.symbols.nautilus_extend_pcr(index, data.byteLength > 0 ? data : null, data.byteLength)
+    return takeString(cstr)
+}
+
+/**
+ * Lock a PCR; it can no longer be extended and is included in attestations
+ */
+export function lockPcr(index: number): void {
+    checkStatus(lib.symbols.nautilus_lock_pcr(index))
+}
+
+/**
+ * NSM module information as reported by DescribeNSM
+ */
+export interface NsmDescription {
+    version: string
+    module_id: string
+    max_pcrs: number
+    locked_pcrs: number[]
+    digest: string
+}
+
+/**
+ * Describe the NSM module (version, module ID, PCR count, locked PCRs, digest)
+ *
+ * @throws NautilusError, e.g. `NsmUnavailable` outside a Nitro Enclave
+ */
+export function describeNsm(): NsmDescription {
+    return JSON.parse(takeString(lib.symbols.nautilus_describe_nsm())) as NsmDescription
+}
+
+/**
+ * Runtime diagnostics; an unreachable NSM is reported in nsm_error
+ */
+export interface Diagnostics {
+    library_version: string
+    mock_nsm: boolean
+    seeded_keys: boolean
+    nsm: NsmDescription | null
+    nsm_error: string | null
+    rng_policy: number
+    last_entropy_source: number
+}
+
+/**
+ * Collect runtime diagnostics for health checks
+ */
+export function getDiagnostics(): Diagnostics {
+    return JSON.parse(takeString(lib.symbols.nautilus_diagnostics())) as Diagnostics
 }
 
 /**
@@ -89,6 +643,7 @@ export function getAttestation(keypair: NautilusKeypair): string {
  * @param timestampMs - Timestamp in milliseconds
  * @param intent - Intent type (default: 0)
  * @returns JSON string with signature and response
+ * @throws NautilusError on unknown intent or invalid arguments
  */
 export function signIntentMessage(
     keypair: NautilusKeypair,
@@ -103,21 +658,307 @@ export function signIntentMessage(
         timestampMs,
         intent
     )
-    // Convert FFI cstring to JavaScript string
-    const result = String(cstr)
-    return result
+    return takeString(cstr)
+}
+
+/**
+ * Sign an intent message with an ECDSA (secp256k1 or secp256r1) key and return JSON response
+ *
+ * @param hash - Message hash, see MessageHash (default: SHA-256)
+ * @param recoverable - Return a 65-byte recoverable signature (r || s || v), with v = 27 / 28
+ *                      for Keccak-256 as EVM ecrecover expects (secp256k1 only), or 0 / 1
+ *                      for SHA-256
+ * @throws NautilusError on unknown intent or hash, a non-ECDSA key, or a recoverable
+ *                       Keccak-256 signature with a secp256r1 key
+ */
+export function signIntentMessageEcdsa(
+    keypair: NautilusKeypair,
+    payload: Buffer,
+    timestampMs: bigint,
+    intent: number = 0,
+    hash: number = MessageHash.Sha256,
+    recoverable: boolean = false
+): string {
+    const cstr = lib.symbols.nautilus_sign_intent_message_ecdsa(
+        keypair,
+        payload.byteLength > 0 ? payload : null,
+        payload.byteLength,
+        timestampMs,
+        intent,
+        hash,
+        recoverable
+    )
+    return takeString(cstr)
+}
+
+/**
+ * Sign an intent message and return the raw signature and BCS-encoded message
+ *
+ * @returns Signature (64 bytes, or 48 for BLS12-381 keys) and the BCS intent message that was signed
+ * @throws NautilusError on unknown intent or invalid arguments
+ */
+export function signIntentMessageBytes(
+    keypair: NautilusKeypair,
+    payload: Buffer,
+    timestampMs: bigint,
+    intent: number = 0
+): { signature: Buffer; intentMessageBcs: Buffer } {
+    const signatureLengthOut = new BigUint64Array(1)
+    const out = takeBuffer(lib.symbols.nautilus_sign_intent_message_bytes(
+        keypair,
+        payload.byteLength > 0 ? payload : null,
+        payload.byteLength,
+        timestampMs,
+        intent,
+        signatureLengthOut
+    ))
+    const signatureLength = Number(signatureLengthOut[0])
+    return { signature: out.subarray(0, signatureLength), intentMessageBcs: out.subarray(signatureLength) }
+}
+
+/**
+ * Aggregate 48-byte BLS12-381 signatures over the same message (e.g. one intent message
+ * signed by several enclave replicas with the same payload and timestamp)
+ *
+ * @returns The 48-byte aggregate signature
+ */
+export function aggregateBlsSignatures(signatures: Buffer[]): Buffer {
+    const all = Buffer.concat(signatures)
+    return takeBuffer(lib.symbols.nautilus_aggregate_bls_signatures(
+        all.byteLength > 0 ? all : null,
+        all.byteLength
+    ))
+}
+
+/**
+ * Verify an aggregate BLS12-381 signature over a message by all given 96-byte public keys.
+ * Only use public keys taken from attestation documents.
+ *
+ * @returns Whether the aggregate signature is valid
+ * @throws NautilusError when no public keys are given
+ */
+export function verifyBlsAggregate(publicKeys: Buffer[], message: Buffer, signature: Buffer): boolean {
+    const keys = Buffer.concat(publicKeys)
+    const code = lib.symbols.nautilus_verify_bls_aggregate(
+        keys.byteLength > 0 ? keys : null,
+        keys.byteLength,
+        message.byteLength > 0 ? message : null,
+        message.byteLength,
+        signature.byteLength > 0 ? signature : null,
+        signature.byteLength
+    )
+    if (code === NautilusErrorCode.InvalidSignature) return false
+    checkStatus(code)
+    return true
+}
+
+/**
+ * Verify a signature from the default signing functions (ECDSA over SHA-256) over a message,
+ * e.g. the BCS intent message returned by signIntentMessageBytes
+ *
+ * @param scheme - Signature scheme of the public key, see KeyScheme
+ * @returns Whether the signature is valid
+ */
+export function verifySignature(
+    scheme: number,
+    publicKey: Buffer,
+    message: Buffer,
+    signature: Buffer
+): boolean {
+    const code = lib.symbols.nautilus_verify_signature(
+        scheme,
+        publicKey.byteLength > 0 ? publicKey : null,
+        publicKey.byteLength,
+        message.byteLength > 0 ? message : null,
+        message.byteLength,
+        signature.byteLength > 0 ? signature : null,
+        signature.byteLength
+    )
+    if (code === NautilusErrorCode.InvalidSignature) return false
+    checkStatus(code)
+    return true
+}
+
+/**
+ * Sui multisig over enclave replica keys; public keys are hex
+ */
+export interface MultiSigCommittee {
+    /** Up to 10 Ed25519, secp256k1 or secp256r1 keys; their order is part of the address */
+    members: { scheme: number; public_key: string; weight: number }[]
+    /** Total weight of signers needed for a valid signature */
+    threshold: number
+}
+
+/**
+ * Sui identity of a multisig committee
+ */
+export interface MultiSigPublicKey {
+    /** 0x-prefixed hex Sui address */
+    address: string
+    /** Base64 flagged multisig public key */
+    public_key: string
+}
+
+/**
+ * Signature by one committee member; fields are hex
+ */
+export interface PartialSignature {
+    public_key: string
+    signature: string
+}
+
+/**
+ * Build the Sui multisig public key and address of a committee of enclave replicas
+ */
+export function buildMultiSigPublicKey(committee: MultiSigCommittee): MultiSigPublicKey {
+    const json = Buffer.from(JSON.stringify(committee))
+    const cstr = lib.symbols.nautilus_build_multisig_public_key(json, json.byteLength)
+    return JSON.parse(takeString(cstr)) as MultiSigPublicKey
+}
+
+/**
+ * Combine members' signatures over the same message (e.g. one intent message signed by
+ * several replicas with the same payload and timestamp) into a Sui multisig signature.
+ * Throws if a signature does not verify or the signers' weights are below the threshold.
+ *
+ * Signatures cover the raw message, not the Blake2b-256(intent || message) digest Sui
+ * signs for transactions and personal messages: Sui nodes and tooling reject the result.
+ * Check it with verifyMultiSig.
+ *
+ * @returns The flagged multisig signature (0x03 || BCS MultiSig)
+ */
+export function combineMultiSigSignatures(
+    committee: MultiSigCommittee,
+    message: Buffer,
+    signatures: PartialSignature[]
+): Buffer {
+    const json = Buffer.from(JSON.stringify({ multisig: committee, signatures }))
+    return takeBuffer(lib.symbols.nautilus_combine_multisig_signatures(
+        json,
+        json.byteLength,
+        message.byteLength > 0 ? message : null,
+        message.byteLength
+    ))
 }
 
 /**
- * Free a keypair (cleanup)
+ * Verify a multisig signature from combineMultiSigSignatures over a message by the committee
+ *
+ * @returns Whether the signature is by the committee and reaches its threshold
+ */
+export function verifyMultiSig(committee: MultiSigCommittee, message: Buffer, signature: Buffer): boolean {
+    const json = Buffer.from(JSON.stringify(committee))
+    const code = lib.symbols.nautilus_verify_multisig(
+        json,
+        json.byteLength,
+        message.byteLength > 0 ? message : null,
+        message.byteLength,
+        signature.byteLength > 0 ? signature : null,
+        signature.byteLength
+    )
+    if (code === NautilusErrorCode.InvalidSignature) return false
+    checkStatus(code)
+    return true
+}
+
+/**
+ * Parameters of the Sui transaction registering an enclave; addresses are hex
+ */
+export interface RegisterEnclaveTxParams {
+    /** Hex attestation document, as returned by getAttestation */
+    attestation: string
+    enclave_package_id: string
+    enclave_config: { object_id: string; initial_shared_version: number }
+    app_package_id: string
+    module_name: string
+    otw_name: string
+    sender: string
+    /** Gas coins; digests are base58 as printed by the Sui CLI */
+    gas_payment: { object_id: string; version: number; digest: string }[]
+    gas_price: number
+    gas_budget: number
+}
+
+/**
+ * Build the unsigned BCS TransactionData calling load_nitro_attestation then
+ * enclave::register_enclave, e.g. for `sui keytool sign --data <base64>`
+ */
+export function buildRegisterEnclaveTx(params: RegisterEnclaveTxParams): Buffer {
+    const json = Buffer.from(JSON.stringify(params))
+    return takeBuffer(lib.symbols.nautilus_build_register_enclave_tx(json, json.byteLength))
+}
+
+/**
+ * Result of rotating a keypair; byte fields are hex
+ */
+export interface KeyRotation {
+    /** Handle of the new keypair (the old handle stays valid until freed) */
+    handle: NautilusKeypair
+    old_public_key: string
+    new_public_key: string
+    timestamp_ms: number
+    reason: string
+    /** BCS IntentMessage<KeyHandover> (intent scope 1) signed by the old key */
+    handover_bcs: string
+    signature: string
+    /** Attestation of the new key, with the Blake2b-256 digest of handover_bcs as user_data */
+    attestation: string
+}
+
+/**
+ * Rotate a keypair: the old key signs a handover to a new attested key of the same scheme.
+ * A name registered for the old key moves to the new one.
+ *
+ * @param reason - Free-form reason recorded in the handover
+ * @param nonce - Optional nonce for the new key's attestation
+ */
+export function rotateKeypair(
+    keypair: NautilusKeypair,
+    reason: string = '',
+    timestampMs: number = Date.now(),
+    nonce?: Buffer
+): KeyRotation {
+    const reasonBytes = Buffer.from(reason, 'utf8')
+    const nonceBuf = nonce ?? Buffer.alloc(0)
+    const cstr = lib.symbols.nautilus_rotate_keypair(
+        keypair,
+        timestampMs,
+        reasonBytes.byteLength > 0 ? reasonBytes : null,
+        reasonBytes.byteLength,
+        nonceBuf.byteLength > 0 ? nonceBuf : null,
+        nonceBuf.byteLength
+    )
+    return JSON.parse(takeString(cstr)) as KeyRotation
+}
+
+/**
+ * Close the shared NSM device handle (cleanup at shutdown); later calls reopen it
+ */
+export function closeNsm(): void {
+    lib.symbols.nautilus_nsm_close()
+}
+
+/**
+ * Free a keypair (cleanup); the handle is invalid afterwards
+ *
+ * @throws NautilusError (InvalidHandle) if the handle is unknown or already freed
  */
 export function freeKeypair(keypair: NautilusKeypair): void {
-    lib.symbols.nautilus_free_keypair(keypair)
+    checkStatus(lib.symbols.nautilus_free_keypair(keypair))
+}
+
+/**
+ * Destroy every keypair and clear cached attestations (e.g. on SIGTERM);
+ * the registry's key memory is wiped and all handles become invalid
+ */
+export function destroyAllKeys(): void {
+    checkStatus(lib.symbols.nautilus_destroy_all_keys())
 }
 
 /**
- * Free a C string (cleanup)
+ * Free a C string returned by a raw `lib.symbols` call (the wrappers above free
+ * their strings themselves)
  */
-export function freeCString(str: string): void {
-    lib.symbols.nautilus_free_cstr(str)
+export function freeCString(str: CString): void {
+    lib.symbols.nautilus_free_cstr(str.ptr)
 }
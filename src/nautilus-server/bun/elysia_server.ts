@@ -17,10 +17,15 @@ import {
   generateKeypair,
   getPublicKeyHex,
   getAttestation,
+  getCachedAttestation,
+  parseAttestation,
+  getDiagnostics,
   signIntentMessage,
   nowMs,
   hexToBytes,
   checkEndpointsStatus,
+  destroyAllKeys,
+  closeNsm,
   type NautilusKeypair
 } from './common'
 
@@ -127,21 +132,55 @@ const app = new Elysia({ tags: ['App'] })
     response: t.String(),
     detail: { summary: 'Ping', tags: ['App'] }
   })
-  .get('/get_attestation', () => {
-    const attestation = getAttestation(keypair)
-    return { attestation }
+  .get('/get_attestation', ({ query }) => {
+    // Reuse the cached document unless the caller supplies a nonce for freshness
+    const nonce = query.nonce ? hexToBytes(query.nonce) : undefined
+    const attestation = getCachedAttestation(keypair, nonce)
+    const document = parseAttestation(attestation)
+    return { attestation, document }
   }, {
-    response: t.Object({ attestation: t.String() }),
+    query: t.Object({
+      nonce: t.Optional(t.String())
+    }),
+    response: t.Object({
+      attestation: t.String(),
+      document: t.Object({
+        module_id: t.String(),
+        digest: t.String(),
+        timestamp: t.Number(),
+        pcrs: t.Record(t.String(), t.String()),
+        certificate: t.String(),
+        cabundle: t.Array(t.String()),
+        public_key: t.Nullable(t.String()),
+        user_data: t.Nullable(t.String()),
+        nonce: t.Nullable(t.String())
+      })
+    }),
     detail: { summary: 'Get Nitro attestation', tags: ['App'] }
   })
   .get('/health_check', async () => {
     const pk = getPublicKeyHex(keypair)
     const status = await checkEndpointsStatus()
-    return { pk, endpoints_status: status }
+    return { pk, endpoints_status: status, diagnostics: getDiagnostics() }
   }, {
     response: t.Object({
       pk: t.String(),
-      endpoints_status: t.Record(t.String(), t.Boolean())
+      endpoints_status: t.Record(t.String(), t.Boolean()),
+      diagnostics: t.Object({
+        library_version: t.String(),
+        mock_nsm: t.Boolean(),
+        seeded_keys: t.Boolean(),
+        nsm: t.Nullable(t.Object({
+          version: t.String(),
+          module_id: t.String(),
+          max_pcrs: t.Number(),
+          locked_pcrs: t.Array(t.Number()),
+          digest: t.String()
+        })),
+        nsm_error: t.Nullable(t.String()),
+        rng_policy: t.Number(),
+        last_entropy_source: t.Number()
+      })
     }),
     detail: { summary: 'Health check', tags: ['App'] }
   })
@@ -271,4 +310,13 @@ const app = new Elysia({ tags: ['App'] })
     reusePort: true
   })
 
-console.log(`🚀 Nautilus server is running on 0.0.0.0:${port}`)
\ No newline at end of file
+console.log(`🚀 Nautilus server is running on 0.0.0.0:${port}`)
+
+// Wipe key material before the enclave shuts down
+for (const signal of ['SIGTERM', 'SIGINT'] as const) {
+  process.on(signal, () => {
+    destroyAllKeys()
+    closeNsm()
+    process.exit(0)
+  })
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of Nitro attestation documents.
+//!
+//! The NSM returns a COSE_Sign1 structure (CBOR array of protected header,
+//! unprotected header, payload, signature) whose payload is the CBOR-encoded
+//! attestation document. This module decodes both layers without verifying
+//! anything; see the verifier for signature and certificate checks.
+use crate::error::{NautilusError, NautilusResult};
+use fastcrypto::encoding::{Encoding, Hex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The four parts of a COSE_Sign1 structure (RFC 8152, section 4.2).
+#[derive(Clone, Debug)]
+pub struct CoseSign1 {
+    /// Serialized protected header (CBOR map, kept as bytes since it is signed as-is).
+    pub protected: Vec<u8>,
+    pub unprotected: serde_cbor::Value,
+    /// CBOR-encoded attestation document.
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct RawCoseSign1(
+    serde_bytes::ByteBuf,
+    serde_cbor::Value,
+    serde_bytes::ByteBuf,
+    serde_bytes::ByteBuf,
+);
+
+impl CoseSign1 {
+    /// Decode a COSE_Sign1 structure, tagged (18) or untagged.
+    pub fn from_bytes(bytes: &[u8]) -> NautilusResult<Self> {
+        let RawCoseSign1(protected, unprotected, payload, signature) =
+            serde_cbor::from_slice(bytes).map_err(|e| {
+                NautilusError::InvalidAttestation(format!("malformed COSE_Sign1: {e}"))
+            })?;
+        Ok(CoseSign1 {
+            protected: protected.into_vec(),
+            unprotected,
+            payload: payload.into_vec(),
+            signature: signature.into_vec(),
+        })
+    }
+
+    /// Decode the attestation document carried in the payload.
+    pub fn document(&self) -> NautilusResult<AttestationDocument> {
+        let doc = nsm_api::api::AttestationDoc::from_binary(&self.payload).map_err(|e| {
+            NautilusError::InvalidAttestation(format!("malformed attestation document: {e:?}"))
+        })?;
+        Ok(AttestationDocument {
+            module_id: doc.module_id,
+            digest: format!("{:?}", doc.digest),
+            timestamp: doc.timestamp,
+            pcrs: doc
+                .pcrs
+                .into_iter()
+                .map(|(index, value)| (index, value.into_vec()))
+                .collect(),
+            certificate: doc.certificate.into_vec(),
+            cabundle: doc.cabundle.into_iter().map(|c| c.into_vec()).collect(),
+            public_key: doc.public_key.map(|b| b.into_vec()),
+            user_data: doc.user_data.map(|b| b.into_vec()),
+            nonce: doc.nonce.map(|b| b.into_vec()),
+        })
+    }
+}
+
+/// A decoded Nitro attestation document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttestationDocument {
+    pub module_id: String,
+    /// Digest algorithm used for PCRs, e.g. `SHA384`.
+    pub digest: String,
+    /// UNIX epoch in milliseconds at which the NSM issued the document.
+    pub timestamp: u64,
+    pub pcrs: BTreeMap<usize, Vec<u8>>,
+    /// DER-encoded enclave certificate that signed the document.
+    pub certificate: Vec<u8>,
+    /// DER-encoded intermediate certificates, root first.
+    pub cabundle: Vec<Vec<u8>>,
+    pub public_key: Option<Vec<u8>>,
+    pub user_data: Option<Vec<u8>>,
+    pub nonce: Option<Vec<u8>>,
+}
+
+/// JSON view of an [`AttestationDocument`] with all byte fields hex-encoded.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AttestationDocumentJson {
+    pub module_id: String,
+    pub digest: String,
+    pub timestamp: u64,
+    pub pcrs: BTreeMap<usize, String>,
+    pub certificate: String,
+    pub cabundle: Vec<String>,
+    pub public_key: Option<String>,
+    pub user_data: Option<String>,
+    pub nonce: Option<String>,
+}
+
+impl From<&AttestationDocument> for AttestationDocumentJson {
+    fn from(doc: &AttestationDocument) -> Self {
+        AttestationDocumentJson {
+            module_id: doc.module_id.clone(),
+            digest: doc.digest.clone(),
+            timestamp: doc.timestamp,
+            pcrs: doc
+                .pcrs
+                .iter()
+                .map(|(index, value)| (*index, Hex::encode(value)))
+                .collect(),
+            certificate: Hex::encode(&doc.certificate),
+            cabundle: doc.cabundle.iter().map(Hex::encode).collect(),
+            public_key: doc.public_key.as_ref().map(Hex::encode),
+            user_data: doc.user_data.as_ref().map(Hex::encode),
+            nonce: doc.nonce.as_ref().map(Hex::encode),
+        }
+    }
+}
+
+/// Decode a raw attestation document as returned by the NSM.
+pub fn parse_attestation(document: &[u8]) -> NautilusResult<AttestationDocument> {
+    CoseSign1::from_bytes(document)?.document()
+}
@@ -0,0 +1,248 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cache of attestation documents per public key.
+//!
+//! A document committing only to a public key (and optional user data) stays
+//! valid for its purpose until the key changes, so it is reused for `ttl` instead
+//! of asking the NSM on every request. Requests carrying a nonce always bypass
+//! the cache, since the nonce must be fresh in every document.
+use crate::error::NautilusResult;
+use crate::nsm;
+use serde_bytes::ByteBuf;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Default time a cached document is reused before being refreshed.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+struct CachedAttestation {
+    user_data: Option<Vec<u8>>,
+    document: Vec<u8>,
+    fetched_at: Instant,
+}
+
+pub struct AttestationCache {
+    ttl: Duration,
+    entries: HashMap<Vec<u8>, CachedAttestation>,
+    /// Bumped by every invalidation, so documents requested before one are not stored.
+    generation: u64,
+}
+
+static CACHE: Mutex<Option<AttestationCache>> = Mutex::new(None);
+
+impl AttestationCache {
+    pub fn new(ttl: Duration) -> Self {
+        AttestationCache {
+            ttl,
+            entries: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    /// Cached document for `public_key` if it is younger than the TTL and was
+    /// requested with the same `user_data`.
+    pub fn lookup(&self, public_key: &[u8], user_data: Option<&[u8]>) -> Option<Vec<u8>> {
+        self.entries
+            .get(public_key)
+            .filter(|entry| {
+                entry.user_data.as_deref() == user_data && entry.fetched_at.elapsed() < self.ttl
+            })
+            .map(|entry| entry.document.clone())
+    }
+
+    /// Current generation, to pass to [`AttestationCache::store`] for a document
+    /// about to be requested.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Cache `document`, fetched for `public_key` and `user_data` at `fetched_at`,
+    /// unless the cache was invalidated or cleared since `generation` was read (the
+    /// key may have been freed while the document was being requested).
+    pub fn store(
+        &mut self,
+        public_key: &[u8],
+        user_data: Option<&[u8]>,
+        document: Vec<u8>,
+        fetched_at: Instant,
+        generation: u64,
+    ) {
+        if generation != self.generation {
+            return;
+        }
+        self.entries.insert(
+            public_key.to_vec(),
+            CachedAttestation {
+                user_data: user_data.map(<[u8]>::to_vec),
+                document,
+                fetched_at,
+            },
+        );
+    }
+
+    /// Drop the cached document for `public_key`, e.g. when the key is freed.
+    pub fn invalidate(&mut self, public_key: &[u8]) {
+        self.entries.remove(public_key);
+        self.generation += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.generation += 1;
+    }
+
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+}
+
+/// Run `f` on the process-wide cache, created with [`DEFAULT_TTL`] on first use.
+/// The cache is locked while `f` runs, so `f` must not wait on the NSM.
+pub fn with_cache<T>(f: impl FnOnce(&mut AttestationCache) -> T) -> T {
+    let mut guard = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    f(guard.get_or_insert_with(|| AttestationCache::new(DEFAULT_TTL)))
+}
+
+/// Return an attestation for `public_key`, from the process-wide cache when it holds
+/// a document younger than the TTL for the same `user_data`. A `nonce` bypasses the
+/// cache entirely, since the nonce must be fresh in every document.
+pub fn get(
+    public_key: &[u8],
+    user_data: Option<&[u8]>,
+    nonce: Option<&[u8]>,
+) -> NautilusResult<Vec<u8>> {
+    if let Some(nonce) = nonce {
+        return nsm::request_attestation(
+            public_key,
+            user_data.map(|d| ByteBuf::from(d.to_vec())),
+            Some(ByteBuf::from(nonce.to_vec())),
+        );
+    }
+    match with_cache(|cache| cache.lookup(public_key, user_data)) {
+        Some(document) => Ok(document),
+        None => refresh(public_key, user_data),
+    }
+}
+
+/// Fetch a new document for `public_key` and `user_data` and cache it. The cache is
+/// not locked during the NSM request, so other keys' lookups are never held up by it;
+/// the document is not cached if the cache was invalidated in the meantime.
+pub fn refresh(public_key: &[u8], user_data: Option<&[u8]>) -> NautilusResult<Vec<u8>> {
+    let generation = with_cache(|cache| cache.generation());
+    let fetched_at = Instant::now();
+    let document = nsm::request_attestation(
+        public_key,
+        user_data.map(|d| ByteBuf::from(d.to_vec())),
+        None,
+    )?;
+    with_cache(|cache| {
+        cache.store(
+            public_key,
+            user_data,
+            document.clone(),
+            fetched_at,
+            generation,
+        )
+    });
+    Ok(document)
+}
+
+#[cfg(all(test, feature = "mock-nsm"))]
+mod tests {
+    use super::*;
+    use crate::attestation::parse_attestation;
+    use crate::key_registry::with_registry;
+    use crate::keys::KeyScheme;
+    use crate::{nautilus_free_keypair, nautilus_generate_keypair};
+
+    fn document(public_key: &[u8]) -> Vec<u8> {
+        nsm::request_attestation(public_key, None, None).unwrap()
+    }
+
+    #[test]
+    fn lookup_expires_after_ttl() {
+        let mut cache = AttestationCache::new(DEFAULT_TTL);
+        let doc = document(b"ttl key");
+        cache.store(
+            b"ttl key",
+            None,
+            doc.clone(),
+            Instant::now(),
+            cache.generation(),
+        );
+        assert_eq!(cache.lookup(b"ttl key", None), Some(doc));
+        assert_eq!(cache.lookup(b"ttl key", Some(b"other data")), None);
+
+        cache.set_ttl(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(cache.lookup(b"ttl key", None), None);
+    }
+
+    #[test]
+    fn zero_ttl_disables_caching() {
+        let mut cache = AttestationCache::new(Duration::ZERO);
+        cache.store(b"key", None, document(b"key"), Instant::now(), 0);
+        assert_eq!(cache.lookup(b"key", None), None);
+    }
+
+    #[test]
+    fn store_after_invalidation_is_dropped() {
+        let mut cache = AttestationCache::new(DEFAULT_TTL);
+        let generation = cache.generation();
+        cache.invalidate(b"freed key");
+        cache.store(
+            b"freed key",
+            None,
+            document(b"freed key"),
+            Instant::now(),
+            generation,
+        );
+        assert_eq!(cache.lookup(b"freed key", None), None);
+
+        let generation = cache.generation();
+        cache.clear();
+        cache.store(
+            b"freed key",
+            None,
+            document(b"freed key"),
+            Instant::now(),
+            generation,
+        );
+        assert_eq!(cache.lookup(b"freed key", None), None);
+    }
+
+    #[test]
+    fn nonce_bypasses_cache() {
+        let first = get(b"nonce key", None, Some(b"nonce 1")).unwrap();
+        let second = get(b"nonce key", None, Some(b"nonce 2")).unwrap();
+        assert_eq!(
+            parse_attestation(&first).unwrap().nonce.as_deref(),
+            Some(&b"nonce 1"[..])
+        );
+        assert_eq!(
+            parse_attestation(&second).unwrap().nonce.as_deref(),
+            Some(&b"nonce 2"[..])
+        );
+        assert_eq!(with_cache(|cache| cache.lookup(b"nonce key", None)), None);
+    }
+
+    #[test]
+    fn freeing_a_key_invalidates_its_documents() {
+        let handle = nautilus_generate_keypair(KeyScheme::Ed25519 as u8);
+        let public_key = with_registry(|registry| registry.get(handle))
+            .unwrap()
+            .public_key_bytes();
+
+        let cached = get(&public_key, None, None).unwrap();
+        assert_eq!(get(&public_key, None, None).unwrap(), cached);
+        assert_eq!(
+            with_cache(|cache| cache.lookup(&public_key, None)),
+            Some(cached)
+        );
+
+        assert_eq!(nautilus_free_keypair(handle), 0);
+        assert_eq!(with_cache(|cache| cache.lookup(&public_key, None)), None);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Print the PCRs of an EIF image (e.g. `out/nitro.eif`) without booting it.
+//!
+//! Usage: `eif_pcrs <path/to/nitro.eif> [--format env|move|cli]`
+//! - `env` (default): `PCR0=<hex>` lines, as in `out/nitro.pcrs`;
+//! - `move`: `x"<hex>"` literals for `create_enclave_config` in Move sources;
+//! - `cli`: `0x<hex>` arguments for `sui client call --function update_pcrs`.
+use fastcrypto::encoding::{Encoding, Hex};
+use nautilus_server::eif;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: eif_pcrs <path/to/nitro.eif> [--format env|move|cli]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, format) = match args.as_slice() {
+        [path] => (path, "env"),
+        [path, flag, format] if flag == "--format" => (path, format.as_str()),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if !matches!(format, "env" | "move" | "cli") {
+        eprintln!("unknown format {format}\n{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let pcrs = match File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| eif::compute_pcrs(&mut BufReader::new(file)).map_err(|e| e.to_string()))
+    {
+        Ok(pcrs) => pcrs,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for (index, value) in [pcrs.pcr0, pcrs.pcr1, pcrs.pcr2].iter().enumerate() {
+        let hex = Hex::encode(value);
+        match format {
+            "move" => println!("x\"{hex}\", // pcr{index}"),
+            "cli" => println!("0x{hex}"),
+            _ => println!("PCR{index}={hex}"),
+        }
+    }
+    ExitCode::SUCCESS
+}
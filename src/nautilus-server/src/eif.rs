@@ -0,0 +1,237 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! PCR prediction from Enclave Image Format (EIF) files.
+//!
+//! Computes PCR0-2 of an enclave image without booting it, as measured by the
+//! Nitro hypervisor and reported by `eif_build --pcrs_output`:
+//! - PCR0: kernel, command line and all ramdisks;
+//! - PCR1: kernel, command line and the first (bootstrap) ramdisk;
+//! - PCR2: the remaining (application) ramdisks.
+//!
+//! Each PCR is `SHA384(0^48 || SHA384(measured section data))`, i.e. one extend
+//! of a zeroed register. Section headers, metadata and signature sections are
+//! not measured.
+use crate::error::{NautilusError, NautilusResult};
+use sha2::{Digest, Sha384};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Magic bytes at the start of every EIF file.
+pub const EIF_MAGIC: [u8; 4] = *b".eif";
+
+/// Maximum number of sections described by the EIF header.
+const MAX_SECTIONS: usize = 32;
+
+/// Size of the fixed EIF header: magic, version, flags, default memory and CPUs,
+/// reserved, section count, section offsets and sizes, unused and CRC32.
+const EIF_HEADER_LEN: usize = 4 + 2 + 2 + 8 + 8 + 2 + 2 + 8 * MAX_SECTIONS * 2 + 4 + 4;
+
+/// Size of a section header: type, flags and size.
+const SECTION_HEADER_LEN: usize = 2 + 2 + 8;
+
+const SECTION_KERNEL: u16 = 1;
+const SECTION_CMDLINE: u16 = 2;
+const SECTION_RAMDISK: u16 = 3;
+
+/// PCR values of an enclave image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EifPcrs {
+    pub pcr0: Vec<u8>,
+    pub pcr1: Vec<u8>,
+    pub pcr2: Vec<u8>,
+}
+
+fn malformed(reason: impl std::fmt::Display) -> NautilusError {
+    NautilusError::InvalidArgument(format!("malformed EIF: {reason}"))
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_be_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn be_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_be_bytes(bytes[at..at + 8].try_into().expect("8 bytes"))
+}
+
+/// Compute PCR0, PCR1 and PCR2 of the EIF image read from `eif`.
+pub fn compute_pcrs<R: Read + Seek>(eif: &mut R) -> NautilusResult<EifPcrs> {
+    let mut header = [0u8; EIF_HEADER_LEN];
+    eif.read_exact(&mut header).map_err(malformed)?;
+    if header[..4] != EIF_MAGIC {
+        return Err(malformed("bad magic"));
+    }
+    let num_sections = be_u16(&header, 26) as usize;
+    if num_sections > MAX_SECTIONS {
+        return Err(malformed(format!("{num_sections} sections")));
+    }
+    let offsets = (0..num_sections).map(|i| be_u64(&header, 28 + 8 * i));
+
+    let (mut image, mut bootstrap, mut application) = (Sha384::new(), Sha384::new(), Sha384::new());
+    let mut ramdisks = 0;
+    for offset in offsets {
+        eif.seek(SeekFrom::Start(offset)).map_err(malformed)?;
+        let mut section = [0u8; SECTION_HEADER_LEN];
+        eif.read_exact(&mut section).map_err(malformed)?;
+        let section_type = be_u16(&section, 0);
+        let hashers: Vec<&mut Sha384> = match section_type {
+            SECTION_KERNEL | SECTION_CMDLINE => vec![&mut image, &mut bootstrap],
+            SECTION_RAMDISK if ramdisks == 0 => vec![&mut image, &mut bootstrap],
+            SECTION_RAMDISK => vec![&mut image, &mut application],
+            _ => continue,
+        };
+        hash_section(eif, be_u64(&section, 4), hashers)?;
+        if section_type == SECTION_RAMDISK {
+            ramdisks += 1;
+        }
+    }
+    if ramdisks == 0 {
+        return Err(malformed("no ramdisk section"));
+    }
+
+    Ok(EifPcrs {
+        pcr0: extend_zero(image),
+        pcr1: extend_zero(bootstrap),
+        pcr2: extend_zero(application),
+    })
+}
+
+/// Feed the next `size` bytes of `eif` into every hasher.
+fn hash_section<R: Read>(
+    eif: &mut R,
+    size: u64,
+    mut hashers: Vec<&mut Sha384>,
+) -> NautilusResult<()> {
+    let mut section = eif.take(size);
+    let mut chunk = vec![0u8; 64 * 1024];
+    let mut remaining = size;
+    while remaining > 0 {
+        let n = section.read(&mut chunk).map_err(malformed)?;
+        if n == 0 {
+            return Err(malformed("truncated section"));
+        }
+        hashers.iter_mut().for_each(|h| h.update(&chunk[..n]));
+        remaining -= n as u64;
+    }
+    Ok(())
+}
+
+/// `SHA384(0^48 || SHA384(data))`: a single extend of a zeroed PCR.
+fn extend_zero(measurement: Sha384) -> Vec<u8> {
+    let digest = measurement.finalize();
+    Sha384::new()
+        .chain_update([0u8; 48])
+        .chain_update(digest)
+        .finalize()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::encoding::{Encoding, Hex};
+    use std::io::Cursor;
+
+    /// PCRs of [`synthetic_eif`], computed independently as
+    /// `SHA384(0^48 || SHA384(section data...))` with Python's `hashlib`.
+    const PCR0: &str = "143a3868119020fbc369fa56dacb9396584dd9a8fd1fcd2461b85b85e1913bde1b5e39f2182a2683acb6090104015d6b";
+    const PCR1: &str = "8cba429f7597b5b5f4d76e0a118afe2b417ecde213fed15945ddd0352d671a465c214e72d91b79fe035fe8879bc3682e";
+    const PCR2: &str = "15ceb42332f9052bf5f8501d40a138e9439a594abb5f342a74da495ce850c4bbfc3e97487c29122e4193cea8e6a2816c";
+
+    /// Section type of the EIF signature section, which is not measured.
+    const SECTION_SIGNATURE: u16 = 4;
+
+    /// An EIF image with the given sections laid out back to back after the header.
+    fn eif_with(sections: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut header = vec![0u8; EIF_HEADER_LEN];
+        header[..4].copy_from_slice(&EIF_MAGIC);
+        header[4..6].copy_from_slice(&4u16.to_be_bytes());
+        header[26..28].copy_from_slice(&(sections.len() as u16).to_be_bytes());
+        let mut body = Vec::new();
+        for (i, (section_type, data)) in sections.iter().enumerate() {
+            let offset = (EIF_HEADER_LEN + body.len()) as u64;
+            header[28 + 8 * i..36 + 8 * i].copy_from_slice(&offset.to_be_bytes());
+            let size_at = 28 + 8 * MAX_SECTIONS + 8 * i;
+            header[size_at..size_at + 8].copy_from_slice(&(data.len() as u64).to_be_bytes());
+            body.extend_from_slice(&section_type.to_be_bytes());
+            body.extend_from_slice(&0u16.to_be_bytes());
+            body.extend_from_slice(&(data.len() as u64).to_be_bytes());
+            body.extend_from_slice(data);
+        }
+        header.extend(body);
+        header
+    }
+
+    fn synthetic_eif() -> Vec<u8> {
+        eif_with(&[
+            (SECTION_KERNEL, b"kernel"),
+            (SECTION_CMDLINE, b"console=ttyS0"),
+            (SECTION_RAMDISK, b"bootstrap ramdisk"),
+            (SECTION_RAMDISK, b"application ramdisk"),
+            (SECTION_SIGNATURE, b"signature"),
+        ])
+    }
+
+    fn assert_malformed(eif: Vec<u8>) {
+        match compute_pcrs(&mut Cursor::new(eif)) {
+            Err(NautilusError::InvalidArgument(reason)) => {
+                assert!(reason.starts_with("malformed EIF"), "{reason}")
+            }
+            other => panic!("expected InvalidArgument, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn synthetic_eif_pcrs() {
+        let pcrs = compute_pcrs(&mut Cursor::new(synthetic_eif())).unwrap();
+        assert_eq!(Hex::encode(pcrs.pcr0), PCR0);
+        assert_eq!(Hex::encode(pcrs.pcr1), PCR1);
+        assert_eq!(Hex::encode(pcrs.pcr2), PCR2);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut eif = synthetic_eif();
+        eif[..4].copy_from_slice(b"elf\0");
+        assert_malformed(eif);
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let mut eif = synthetic_eif();
+        eif.truncate(EIF_HEADER_LEN - 1);
+        assert_malformed(eif);
+    }
+
+    #[test]
+    fn rejects_too_many_sections() {
+        let mut eif = synthetic_eif();
+        eif[26..28].copy_from_slice(&(MAX_SECTIONS as u16 + 1).to_be_bytes());
+        assert_malformed(eif);
+    }
+
+    #[test]
+    fn rejects_out_of_range_section_offset() {
+        let mut eif = synthetic_eif();
+        let past_end = eif.len() as u64 + 1;
+        eif[28..36].copy_from_slice(&past_end.to_be_bytes());
+        assert_malformed(eif);
+    }
+
+    #[test]
+    fn rejects_truncated_section() {
+        let mut eif = eif_with(&[
+            (SECTION_KERNEL, b"kernel"),
+            (SECTION_RAMDISK, b"bootstrap ramdisk"),
+        ]);
+        eif.truncate(eif.len() - 3);
+        assert_malformed(eif);
+    }
+
+    #[test]
+    fn rejects_missing_ramdisk() {
+        assert_malformed(eif_with(&[
+            (SECTION_KERNEL, b"kernel"),
+            (SECTION_CMDLINE, b"console=ttyS0"),
+        ]));
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Error model shared by all FFI exports.
+//!
+//! Every exported function records its outcome in a thread-local slot before
+//! returning: `NAUTILUS_OK` on success, or the error code and message on
+//! failure. Functions returning pointers return NULL on failure. The host reads
+//! the outcome with `nautilus_last_error_code` / `nautilus_last_error_message`
+//! on the same thread, right after the call.
+//!
+//! Exports run their body through `ffi_call`, which also catches panics
+//! (from fastcrypto, bcs, CString, ...) and reports them as `Panic` instead
+//! of unwinding across the `extern "C"` boundary.
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Status code for a successful call.
+pub const NAUTILUS_OK: i32 = 0;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NautilusError {
+    /// The NSM device could not be opened (e.g. not running inside a Nitro Enclave).
+    NsmUnavailable,
+    /// The NSM driver returned an error or an unexpected response.
+    NsmRequestFailed(String),
+    /// A NULL or otherwise unusable pointer argument.
+    InvalidPointer(&'static str),
+    /// BCS/JSON serialization or C string conversion failed.
+    Serialization(String),
+    /// The intent byte does not map to a known `IntentScope`.
+    UnknownIntent(u8),
+    /// A panic was caught at the FFI boundary.
+    Panic(String),
+    /// An attestation document could not be decoded.
+    InvalidAttestation(String),
+    /// An argument value is out of range or malformed.
+    InvalidArgument(String),
+    /// A signature does not verify, or the key or signature is malformed.
+    InvalidSignature(String),
+    /// A keypair handle that was never issued or has been freed.
+    InvalidHandle(u64),
+}
+
+impl NautilusError {
+    /// Stable numeric code reported across the FFI.
+    pub fn code(&self) -> i32 {
+        match self {
+            NautilusError::NsmUnavailable => 1,
+            NautilusError::NsmRequestFailed(_) => 2,
+            NautilusError::InvalidPointer(_) => 3,
+            NautilusError::Serialization(_) => 4,
+            NautilusError::UnknownIntent(_) => 5,
+            NautilusError::Panic(_) => 6,
+            NautilusError::InvalidAttestation(_) => 7,
+            NautilusError::InvalidArgument(_) => 8,
+            NautilusError::InvalidSignature(_) => 9,
+            NautilusError::InvalidHandle(_) => 10,
+        }
+    }
+}
+
+impl fmt::Display for NautilusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NautilusError::NsmUnavailable => {
+                write!(
+                    f,
+                    "NSM device unavailable (not running inside a Nitro Enclave?)"
+                )
+            }
+            NautilusError::NsmRequestFailed(msg) => write!(f, "NSM request failed: {msg}"),
+            NautilusError::InvalidPointer(name) => write!(f, "invalid pointer: {name}"),
+            NautilusError::Serialization(msg) => write!(f, "serialization failed: {msg}"),
+            NautilusError::UnknownIntent(intent) => write!(f, "unknown intent scope: {intent}"),
+            NautilusError::Panic(msg) => write!(f, "panic in nautilus library: {msg}"),
+            NautilusError::InvalidAttestation(msg) => write!(f, "invalid attestation: {msg}"),
+            NautilusError::InvalidArgument(msg) => write!(f, "invalid argument: {msg}"),
+            NautilusError::InvalidSignature(msg) => write!(f, "invalid signature: {msg}"),
+            NautilusError::InvalidHandle(handle) => {
+                write!(f, "unknown or freed keypair handle: {handle}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NautilusError {}
+
+pub type NautilusResult<T> = Result<T, NautilusError>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<NautilusError>> = const { RefCell::new(None) };
+}
+
+/// Record the outcome of the current FFI call for this thread.
+pub(crate) fn set_last_error(err: Option<NautilusError>) {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = err);
+}
+
+/// Record the outcome of `result` and hand back its value, if any.
+fn record<T>(result: NautilusResult<T>) -> Option<T> {
+    match result {
+        Ok(value) => {
+            set_last_error(None);
+            Some(value)
+        }
+        Err(err) => {
+            set_last_error(Some(err));
+            None
+        }
+    }
+}
+
+/// Run the body of an FFI export, catching any panic so it never unwinds into the host.
+/// Records the outcome for `nautilus_last_error_*` and returns `on_error` on failure.
+pub(crate) fn ffi_call<T>(on_error: T, f: impl FnOnce() -> NautilusResult<T>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        Err(NautilusError::Panic(msg))
+    });
+    record(result).unwrap_or(on_error)
+}
+
+/// Status code of the last FFI call made on this thread (`NAUTILUS_OK` on success).
+#[no_mangle]
+pub extern "C" fn nautilus_last_error_code() -> i32 {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(NAUTILUS_OK, NautilusError::code)
+    })
+}
+
+/// Message describing the last error on this thread, or NULL if the last call succeeded.
+/// Returns a newly allocated C string; caller must free via `nautilus_free_cstr`.
+/// Reading the message does not clear it.
+#[no_mangle]
+pub extern "C" fn nautilus_last_error_message() -> *mut c_char {
+    catch_unwind(|| {
+        LAST_ERROR.with(|slot| match slot.borrow().as_ref() {
+            // Strip NULs so the conversion cannot fail.
+            Some(err) => CString::new(err.to_string().replace('\0', ""))
+                .map_or(std::ptr::null_mut(), CString::into_raw),
+            None => std::ptr::null_mut(),
+        })
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    /// `nautilus_last_error_message`, copied and freed.
+    fn last_error_message() -> Option<String> {
+        let message = nautilus_last_error_message();
+        if message.is_null() {
+            return None;
+        }
+        // SAFETY: non-NULL results are C strings allocated by the library.
+        let text = unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned();
+        crate::nautilus_free_cstr(message);
+        Some(text)
+    }
+
+    #[test]
+    fn panic_returns_sentinel_and_records_error() {
+        let handle = 42u64;
+        assert_eq!(
+            ffi_call(0, || -> NautilusResult<u64> { panic!("boom {handle}") }),
+            0
+        );
+        assert_eq!(
+            nautilus_last_error_code(),
+            NautilusError::Panic(String::new()).code()
+        );
+        let message = last_error_message().unwrap();
+        assert!(message.contains("boom 42"), "{message}");
+
+        assert_eq!(
+            ffi_call(-1, || -> NautilusResult<i32> { panic!("static") }),
+            -1
+        );
+        assert!(last_error_message().unwrap().contains("static"));
+    }
+
+    #[test]
+    fn success_clears_last_error() {
+        let fail = || {
+            ffi_call(-1, || {
+                Err(NautilusError::InvalidArgument("bad".to_string()))
+            })
+        };
+        assert_eq!(fail(), -1);
+        assert_eq!(nautilus_last_error_code(), 8);
+        assert_eq!(
+            last_error_message().as_deref(),
+            Some("invalid argument: bad")
+        );
+
+        assert_eq!(fail(), -1);
+        assert_eq!(ffi_call(-1, || Ok(7)), 7);
+        assert_eq!(nautilus_last_error_code(), NAUTILUS_OK);
+        assert_eq!(last_error_message(), None);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key bundles: several enclave public keys vouched for by one attestation.
+//!
+//! The document's `public_key` stays the Ed25519 signing key (as expected by
+//! `enclave::register_enclave`), while `user_data` carries the Blake2b-256 digest
+//! of the BCS-encoded [`KeyBundle`]. Clients that receive the bundle bytes next to
+//! the document recompute the digest with [`KeyBundle::check_document`], then use
+//! e.g. the encryption key to send inputs to the enclave.
+use crate::attestation::AttestationDocument;
+use crate::error::{NautilusError, NautilusResult};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::{Deserialize, Serialize};
+
+/// Current BCS layout of [`KeyBundle`].
+pub const KEY_BUNDLE_VERSION: u8 = 1;
+
+/// What a bundled key is used for. BCS-encoded as its variant index.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPurpose {
+    Signing,
+    Encryption,
+    Session,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BundledKey {
+    pub purpose: KeyPurpose,
+    pub public_key: Vec<u8>,
+}
+
+/// Public keys committed in one attestation, signing key first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyBundle {
+    pub version: u8,
+    pub keys: Vec<BundledKey>,
+}
+
+/// Bundled key as exchanged with hosts, with a hex-encoded public key.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundledKeyJson {
+    pub purpose: KeyPurpose,
+    pub public_key: String,
+}
+
+/// Decoded bundle as returned to hosts.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyBundleJson {
+    pub version: u8,
+    pub keys: Vec<BundledKeyJson>,
+    /// Hex Blake2b-256 digest of the BCS bundle, as found in the document's `user_data`.
+    pub digest: String,
+}
+
+impl KeyBundle {
+    /// Bundle `signing_key` followed by `additional` keys.
+    pub fn new(signing_key: &[u8], additional: Vec<BundledKey>) -> Self {
+        let mut keys = vec![BundledKey {
+            purpose: KeyPurpose::Signing,
+            public_key: signing_key.to_vec(),
+        }];
+        keys.extend(additional);
+        KeyBundle {
+            version: KEY_BUNDLE_VERSION,
+            keys,
+        }
+    }
+
+    /// Parse additional keys from host JSON: `[{ "purpose": "encryption", "public_key": <hex> }]`.
+    pub fn additional_keys_from_json(json: &[u8]) -> NautilusResult<Vec<BundledKey>> {
+        let keys: Vec<BundledKeyJson> = serde_json::from_slice(json)
+            .map_err(|e| NautilusError::InvalidArgument(format!("invalid key list: {e}")))?;
+        keys.into_iter()
+            .map(|key| {
+                if key.purpose == KeyPurpose::Signing {
+                    return Err(NautilusError::InvalidArgument(
+                        "the signing key is bundled from the keypair".to_string(),
+                    ));
+                }
+                let public_key = Hex::decode(key.public_key.trim_start_matches("0x"))
+                    .map_err(|e| NautilusError::InvalidArgument(format!("invalid key hex: {e}")))?;
+                Ok(BundledKey {
+                    purpose: key.purpose,
+                    public_key,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_bcs(&self) -> NautilusResult<Vec<u8>> {
+        bcs::to_bytes(self).map_err(|e| NautilusError::Serialization(e.to_string()))
+    }
+
+    pub fn from_bcs(bytes: &[u8]) -> NautilusResult<Self> {
+        let bundle: KeyBundle = bcs::from_bytes(bytes)
+            .map_err(|e| NautilusError::InvalidArgument(format!("malformed key bundle: {e}")))?;
+        if bundle.version != KEY_BUNDLE_VERSION {
+            return Err(NautilusError::InvalidArgument(format!(
+                "unsupported key bundle version {}",
+                bundle.version
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// Blake2b-256 digest of the BCS encoding, committed as the document's `user_data`.
+    pub fn digest(&self) -> NautilusResult<[u8; 32]> {
+        Ok(Blake2b256::digest(self.to_bcs()?).digest)
+    }
+
+    /// The signing key, which must also be the document's `public_key`.
+    pub fn signing_key(&self) -> Option<&[u8]> {
+        self.keys
+            .iter()
+            .find(|key| key.purpose == KeyPurpose::Signing)
+            .map(|key| key.public_key.as_slice())
+    }
+
+    /// Check that `doc` attests this bundle: `user_data` is the bundle digest and
+    /// `public_key` is the bundled signing key. Does not verify the document itself.
+    pub fn check_document(&self, doc: &AttestationDocument) -> NautilusResult<()> {
+        if doc.user_data.as_deref() != Some(&self.digest()?[..]) {
+            return Err(NautilusError::InvalidAttestation(
+                "user_data is not the key bundle digest".to_string(),
+            ));
+        }
+        if doc.public_key.as_deref() != self.signing_key() {
+            return Err(NautilusError::InvalidAttestation(
+                "public_key is not the bundled signing key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> NautilusResult<KeyBundleJson> {
+        Ok(KeyBundleJson {
+            version: self.version,
+            keys: self
+                .keys
+                .iter()
+                .map(|key| BundledKeyJson {
+                    purpose: key.purpose,
+                    public_key: Hex::encode(&key.public_key),
+                })
+                .collect(),
+            digest: Hex::encode(self.digest()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BCS of [`bundle`]: version, key count, then purpose index, length and bytes
+    /// of each key. The digest was computed independently with Python's `hashlib`.
+    const BUNDLE_V1: &str = "0102\
+        0020 1111111111111111111111111111111111111111111111111111111111111111\
+        0120 2222222222222222222222222222222222222222222222222222222222222222";
+    const BUNDLE_V1_DIGEST: &str =
+        "7a6816282b12032e7d5ed3db7b31d9a8aae36fa0bd768c34f0c4338ff1a415f1";
+
+    fn bundle() -> KeyBundle {
+        KeyBundle::new(
+            &[0x11; 32],
+            vec![BundledKey {
+                purpose: KeyPurpose::Encryption,
+                public_key: vec![0x22; 32],
+            }],
+        )
+    }
+
+    #[test]
+    fn v1_layout_is_pinned() {
+        let golden = Hex::decode(&BUNDLE_V1.replace(' ', "")).unwrap();
+        assert_eq!(bundle().to_bcs().unwrap(), golden);
+        assert_eq!(Hex::encode(bundle().digest().unwrap()), BUNDLE_V1_DIGEST);
+        assert_eq!(KeyBundle::from_bcs(&golden).unwrap(), bundle());
+    }
+
+    #[test]
+    fn from_bcs_rejects_other_versions() {
+        let mut bytes = bundle().to_bcs().unwrap();
+        bytes[0] = KEY_BUNDLE_VERSION + 1;
+        assert!(matches!(
+            KeyBundle::from_bcs(&bytes),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(feature = "mock-nsm")]
+    #[test]
+    fn attested_bundle_checks_against_its_document() {
+        use crate::attestation::parse_attestation;
+        use crate::keys::KeyScheme;
+        use crate::{attest_key_bundle, nautilus_free_keypair, nautilus_generate_keypair};
+
+        let handle = nautilus_generate_keypair(KeyScheme::Ed25519 as u8);
+        let keys_json = br#"[{ "purpose": "encryption", "public_key": "0x2222" }]"#;
+        let json = attest_key_bundle(
+            handle,
+            keys_json.as_ptr(),
+            keys_json.len(),
+            std::ptr::null(),
+            0,
+        )
+        .unwrap();
+        assert_eq!(nautilus_free_keypair(handle), 0);
+
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let decode = |field: &str| Hex::decode(json[field].as_str().unwrap()).unwrap();
+        let doc = parse_attestation(&decode("attestation")).unwrap();
+        let bundle = KeyBundle::from_bcs(&decode("bundle")).unwrap();
+        assert_eq!(bundle.keys[1].public_key, vec![0x22, 0x22]);
+        bundle.check_document(&doc).unwrap();
+
+        for index in 0..bundle.keys.len() {
+            let mut tampered = bundle.clone();
+            tampered.keys[index].public_key[0] ^= 1;
+            assert!(matches!(
+                tampered.check_document(&doc),
+                Err(NautilusError::InvalidAttestation(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registry of live keypairs, addressed by opaque integer handles.
+//!
+//! The host never holds a pointer to key material: generating a keypair returns a
+//! handle, every FFI call looks the handle up here, and freeing removes it. Handles
+//! are never reused, so a stale or forged handle fails with `InvalidHandle` instead
+//! of touching freed memory. Keys can also be registered under a name (e.g.
+//! `"signing"`, `"session"`) and found again by it.
+//!
+//! Keypairs are stored in [`SecureBox`]es, so the registry's copy is locked where
+//! possible and wiped when the last reference is dropped (temporaries left while
+//! generating the key are not, see [`crate::secure_box`]).
+use crate::error::{NautilusError, NautilusResult};
+use crate::keys::EnclaveKeyPair;
+use crate::secure_box::SecureBox;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Opaque keypair handle handed to the host.
+pub type KeyHandle = u64;
+
+/// Never a valid handle; returned by FFI calls that fail to create a keypair.
+pub const INVALID_HANDLE: KeyHandle = 0;
+
+/// Keys are shared with in-flight calls, so a handle freed during a slow NSM request
+/// only drops the key once that request completes.
+pub struct KeyRegistry {
+    next_handle: KeyHandle,
+    keys: HashMap<KeyHandle, Arc<SecureBox<EnclaveKeyPair>>>,
+    names: HashMap<String, KeyHandle>,
+}
+
+static REGISTRY: Mutex<Option<KeyRegistry>> = Mutex::new(None);
+
+impl Default for KeyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyRegistry {
+    pub fn new() -> Self {
+        KeyRegistry {
+            next_handle: INVALID_HANDLE + 1,
+            keys: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// Register `kp`, optionally under a `name` not already in use, and return its handle.
+    pub fn insert(&mut self, kp: EnclaveKeyPair, name: Option<&str>) -> NautilusResult<KeyHandle> {
+        if let Some(name) = name {
+            if name.is_empty() {
+                return Err(NautilusError::InvalidArgument("empty key name".to_string()));
+            }
+            if self.names.contains_key(name) {
+                return Err(NautilusError::InvalidArgument(format!(
+                    "key name already in use: {name}"
+                )));
+            }
+        }
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.keys.insert(handle, Arc::new(SecureBox::new(kp)));
+        if let Some(name) = name {
+            self.names.insert(name.to_string(), handle);
+        }
+        Ok(handle)
+    }
+
+    /// Register `kp` as the successor of the live handle `old`, moving `old`'s name to it.
+    pub fn insert_rotated(
+        &mut self,
+        kp: EnclaveKeyPair,
+        old: KeyHandle,
+    ) -> NautilusResult<KeyHandle> {
+        self.get(old)?;
+        let handle = self.insert(kp, None)?;
+        self.names
+            .values_mut()
+            .filter(|named| **named == old)
+            .for_each(|named| *named = handle);
+        Ok(handle)
+    }
+
+    /// Return the keypair behind a live `handle`.
+    pub fn get(&self, handle: KeyHandle) -> NautilusResult<Arc<SecureBox<EnclaveKeyPair>>> {
+        self.keys
+            .get(&handle)
+            .cloned()
+            .ok_or(NautilusError::InvalidHandle(handle))
+    }
+
+    /// Return the handle of the keypair registered under `name`.
+    pub fn handle_by_name(&self, name: &str) -> NautilusResult<KeyHandle> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| NautilusError::InvalidArgument(format!("no key named {name}")))
+    }
+
+    /// Unregister a live `handle` (and its name), returning its keypair.
+    pub fn remove(&mut self, handle: KeyHandle) -> NautilusResult<Arc<SecureBox<EnclaveKeyPair>>> {
+        let kp = self
+            .keys
+            .remove(&handle)
+            .ok_or(NautilusError::InvalidHandle(handle))?;
+        self.names.retain(|_, named| *named != handle);
+        Ok(kp)
+    }
+
+    /// Unregister every keypair, returning them; their handles are never valid again.
+    pub fn clear(&mut self) -> Vec<Arc<SecureBox<EnclaveKeyPair>>> {
+        self.names.clear();
+        self.keys.drain().map(|(_, kp)| kp).collect()
+    }
+}
+
+/// Run `f` on the process-wide registry, created on first use.
+pub fn with_registry<T>(f: impl FnOnce(&mut KeyRegistry) -> T) -> T {
+    let mut guard = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    f(guard.get_or_insert_with(KeyRegistry::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::KeyScheme;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn keypair(seed: u64) -> EnclaveKeyPair {
+        EnclaveKeyPair::generate(KeyScheme::Ed25519, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn public_key(registry: &KeyRegistry, handle: KeyHandle) -> Vec<u8> {
+        registry.get(handle).unwrap().public_key_bytes()
+    }
+
+    #[test]
+    fn handles_start_at_one_and_are_never_reused() {
+        let mut registry = KeyRegistry::new();
+        let first = registry.insert(keypair(1), None).unwrap();
+        let second = registry.insert(keypair(2), None).unwrap();
+        assert_eq!((first, second), (1, 2));
+
+        registry.remove(second).unwrap();
+        assert_eq!(registry.insert(keypair(3), None).unwrap(), 3);
+        registry.clear();
+        assert_eq!(registry.insert(keypair(4), None).unwrap(), 4);
+    }
+
+    #[test]
+    fn freed_handles_are_invalid() {
+        let mut registry = KeyRegistry::new();
+        let handle = registry.insert(keypair(1), None).unwrap();
+        registry.remove(handle).unwrap();
+        assert!(
+            matches!(registry.get(handle), Err(NautilusError::InvalidHandle(h)) if h == handle)
+        );
+        assert!(matches!(
+            registry.remove(handle),
+            Err(NautilusError::InvalidHandle(_))
+        ));
+        assert!(matches!(
+            registry.get(INVALID_HANDLE),
+            Err(NautilusError::InvalidHandle(0))
+        ));
+    }
+
+    #[test]
+    fn names_are_unique_among_live_keys() {
+        let mut registry = KeyRegistry::new();
+        let signing = registry.insert(keypair(1), Some("signing")).unwrap();
+        assert_eq!(registry.handle_by_name("signing").unwrap(), signing);
+        assert!(matches!(
+            registry.insert(keypair(2), Some("signing")),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.insert(keypair(2), Some("")),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+
+        registry.remove(signing).unwrap();
+        assert!(registry.handle_by_name("signing").is_err());
+        let reused = registry.insert(keypair(2), Some("signing")).unwrap();
+        assert_ne!(reused, signing);
+        assert_eq!(registry.handle_by_name("signing").unwrap(), reused);
+    }
+
+    #[test]
+    fn insert_rotated_moves_the_name_to_the_new_key() {
+        let mut registry = KeyRegistry::new();
+        let old = registry.insert(keypair(1), Some("signing")).unwrap();
+        let new = registry.insert_rotated(keypair(2), old).unwrap();
+
+        assert_eq!(registry.handle_by_name("signing").unwrap(), new);
+        assert_eq!(public_key(&registry, new), keypair(2).public_key_bytes());
+        assert_eq!(public_key(&registry, old), keypair(1).public_key_bytes());
+
+        registry.remove(old).unwrap();
+        assert_eq!(registry.handle_by_name("signing").unwrap(), new);
+        assert!(matches!(
+            registry.insert_rotated(keypair(3), old),
+            Err(NautilusError::InvalidHandle(_))
+        ));
+    }
+}
@@ -0,0 +1,645 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enclave keypairs for each supported signature scheme.
+//!
+//! Scheme identifiers are the Sui signature scheme flags, so the same value can be
+//! used on-chain. Ed25519 signs messages directly; ECDSA schemes (secp256k1, and
+//! secp256r1 for WebCrypto/HSM verifiers) hash the message first, with SHA-256 by
+//! default (as expected by Sui's `ecdsa_k1` and `ecdsa_r1`) or Keccak-256 for EVM
+//! verifiers, and can produce recoverable signatures (`r || s || v`).
+//!
+//! BLS12-381 keys use the min-sig variant (48-byte signatures, 96-byte public keys,
+//! as Sui validators do), so signatures from several enclave replicas over the same
+//! message combine into one with [`aggregate_bls_signatures`].
+//!
+//! The Sui address of a key is the Blake2b-256 of its flagged public key
+//! (`flag || public key`), which Sui also prints in base64 (e.g. `sui keytool list`).
+//! [`PublicKeyEncoding`] selects this and the other textual forms of a public key.
+//!
+//! With the `seeded-keys` feature, [`EnclaveKeyPair::from_seed`] derives keypairs
+//! deterministically for test vectors; enclave keys must always come from
+//! [`EnclaveKeyPair::generate`].
+use crate::error::{NautilusError, NautilusResult};
+use crate::sui_tx::Address;
+use fastcrypto::bls12381::min_sig::{
+    BLS12381AggregateSignature, BLS12381KeyPair, BLS12381PublicKey, BLS12381Signature,
+};
+use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PublicKey, Ed25519Signature};
+use fastcrypto::encoding::{Base58, Base64, Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction, Keccak256, Sha256};
+use fastcrypto::secp256k1::{Secp256k1KeyPair, Secp256k1PublicKey, Secp256k1Signature};
+use fastcrypto::secp256r1::{Secp256r1KeyPair, Secp256r1PublicKey, Secp256r1Signature};
+use fastcrypto::traits::{
+    AggregateAuthenticator, KeyPair, RecoverableSigner, Signer, ToFromBytes, VerifyingKey,
+};
+use rand::rngs::StdRng;
+
+/// Signature scheme of a keypair, as its Sui flag byte.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyScheme {
+    Ed25519 = 0x00,
+    Secp256k1 = 0x01,
+    Secp256r1 = 0x02,
+    /// BLS12-381 min-sig (flag `0x03` is Sui multisig, not a key scheme).
+    Bls12381 = 0x04,
+}
+
+impl TryFrom<u8> for KeyScheme {
+    type Error = NautilusError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(KeyScheme::Ed25519),
+            0x01 => Ok(KeyScheme::Secp256k1),
+            0x02 => Ok(KeyScheme::Secp256r1),
+            0x04 => Ok(KeyScheme::Bls12381),
+            other => Err(NautilusError::InvalidArgument(format!(
+                "unknown key scheme: {other}"
+            ))),
+        }
+    }
+}
+
+/// Hash applied to the message before ECDSA signing.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageHash {
+    /// SHA-256, the default for Sui ECDSA signatures.
+    Sha256 = 0,
+    /// Keccak-256, as used by EVM `ecrecover` (recovery ids are then `27` / `28`).
+    Keccak256 = 1,
+}
+
+impl TryFrom<u8> for MessageHash {
+    type Error = NautilusError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MessageHash::Sha256),
+            1 => Ok(MessageHash::Keccak256),
+            other => Err(NautilusError::InvalidArgument(format!(
+                "unknown message hash: {other}"
+            ))),
+        }
+    }
+}
+
+/// Textual representation of a public key.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicKeyEncoding {
+    /// Lowercase hex without prefix.
+    Hex = 0,
+    /// Lowercase hex with a `0x` prefix.
+    PrefixedHex = 1,
+    /// Standard base64 of the raw key.
+    Base64 = 2,
+    /// Base64 of the Sui flagged key (`flag || public key`).
+    SuiBase64 = 3,
+    /// Multibase base58btc (`z...`) of the multicodec-prefixed key.
+    Multibase = 4,
+    /// `did:key:` followed by the multibase form.
+    DidKey = 5,
+}
+
+impl TryFrom<u8> for PublicKeyEncoding {
+    type Error = NautilusError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PublicKeyEncoding::Hex),
+            1 => Ok(PublicKeyEncoding::PrefixedHex),
+            2 => Ok(PublicKeyEncoding::Base64),
+            3 => Ok(PublicKeyEncoding::SuiBase64),
+            4 => Ok(PublicKeyEncoding::Multibase),
+            5 => Ok(PublicKeyEncoding::DidKey),
+            other => Err(NautilusError::InvalidArgument(format!(
+                "unknown public key encoding: {other}"
+            ))),
+        }
+    }
+}
+
+impl KeyScheme {
+    /// Unsigned-varint multicodec code of the scheme's (compressed) public key type:
+    /// `ed25519-pub` (0xed), `secp256k1-pub` (0xe7), `p256-pub` (0x1200) or
+    /// `bls12_381-g2-pub` (0xeb).
+    fn multicodec_prefix(self) -> &'static [u8] {
+        match self {
+            KeyScheme::Ed25519 => &[0xed, 0x01],
+            KeyScheme::Secp256k1 => &[0xe7, 0x01],
+            KeyScheme::Secp256r1 => &[0x80, 0x24],
+            KeyScheme::Bls12381 => &[0xeb, 0x01],
+        }
+    }
+}
+
+/// HKDF salt of [`EnclaveKeyPair::from_seed`], separating seeded keys from other uses of the seed.
+#[cfg(feature = "seeded-keys")]
+pub const SEED_SALT: &[u8] = b"nautilus-seeded-keypair-v1";
+
+/// RNG yielding the 32 bytes of a seeded BLS `KeyGen` IKM, once, to fastcrypto's
+/// [`BLS12381KeyPair::generate`], which draws exactly 32 bytes of IKM.
+#[cfg(feature = "seeded-keys")]
+struct SeedRng(zeroize::Zeroizing<[u8; 32]>);
+
+#[cfg(feature = "seeded-keys")]
+impl rand::RngCore for SeedRng {
+    fn next_u32(&mut self) -> u32 {
+        unreachable!("seeded BLS key generation only draws bytes")
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        unreachable!("seeded BLS key generation only draws bytes")
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert_eq!(
+            dest.len(),
+            32,
+            "seeded BLS key generation draws a 32-byte IKM"
+        );
+        dest.copy_from_slice(self.0.as_slice());
+        zeroize::Zeroize::zeroize(&mut *self.0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "seeded-keys")]
+impl rand::CryptoRng for SeedRng {}
+
+#[cfg(feature = "seeded-keys")]
+impl fastcrypto::traits::AllowedRng for SeedRng {}
+
+/// An enclave keypair of any supported scheme.
+// Keypairs always live on the heap in the key registry, so the variant size gap is moot.
+#[allow(clippy::large_enum_variant)]
+pub enum EnclaveKeyPair {
+    Ed25519(Ed25519KeyPair),
+    Secp256k1(Secp256k1KeyPair),
+    Secp256r1(Secp256r1KeyPair),
+    Bls12381(BLS12381KeyPair),
+}
+
+impl From<Ed25519KeyPair> for EnclaveKeyPair {
+    fn from(kp: Ed25519KeyPair) -> Self {
+        EnclaveKeyPair::Ed25519(kp)
+    }
+}
+
+impl From<Secp256k1KeyPair> for EnclaveKeyPair {
+    fn from(kp: Secp256k1KeyPair) -> Self {
+        EnclaveKeyPair::Secp256k1(kp)
+    }
+}
+
+impl From<Secp256r1KeyPair> for EnclaveKeyPair {
+    fn from(kp: Secp256r1KeyPair) -> Self {
+        EnclaveKeyPair::Secp256r1(kp)
+    }
+}
+
+impl From<BLS12381KeyPair> for EnclaveKeyPair {
+    fn from(kp: BLS12381KeyPair) -> Self {
+        EnclaveKeyPair::Bls12381(kp)
+    }
+}
+
+impl EnclaveKeyPair {
+    pub fn generate(scheme: KeyScheme, rng: &mut StdRng) -> Self {
+        match scheme {
+            KeyScheme::Ed25519 => Ed25519KeyPair::generate(rng).into(),
+            KeyScheme::Secp256k1 => Secp256k1KeyPair::generate(rng).into(),
+            KeyScheme::Secp256r1 => Secp256r1KeyPair::generate(rng).into(),
+            KeyScheme::Bls12381 => BLS12381KeyPair::generate(rng).into(),
+        }
+    }
+
+    /// Derive a keypair deterministically from `seed` (`seeded-keys` feature only).
+    ///
+    /// The private key is the first 32 bytes of
+    /// `HKDF-SHA256(ikm = seed, salt = SEED_SALT, info = [scheme flag])`, so other
+    /// languages can reproduce it with any RFC 5869 implementation. For BLS12-381 these
+    /// bytes are instead the IKM of the standard BLS `KeyGen` (empty key info), as most
+    /// of them are not valid scalars; fastcrypto's key generation draws exactly that IKM. Fails for an empty seed, or in the negligible case
+    /// where the output is not a valid ECDSA scalar.
+    #[cfg(feature = "seeded-keys")]
+    pub fn from_seed(scheme: KeyScheme, seed: &[u8]) -> NautilusResult<Self> {
+        use fastcrypto::ed25519::Ed25519PrivateKey;
+        use fastcrypto::secp256k1::Secp256k1PrivateKey;
+        use fastcrypto::secp256r1::Secp256r1PrivateKey;
+
+        if seed.is_empty() {
+            return Err(NautilusError::InvalidArgument("empty seed".to_string()));
+        }
+        let mut okm = zeroize::Zeroizing::new([0u8; 32]);
+        hkdf::Hkdf::<sha2::Sha256>::new(Some(SEED_SALT), seed)
+            .expand(&[scheme as u8], okm.as_mut())
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        let invalid = |_| NautilusError::InvalidArgument("seed derives an invalid key".to_string());
+        Ok(match scheme {
+            KeyScheme::Ed25519 => Ed25519KeyPair::from(
+                Ed25519PrivateKey::from_bytes(okm.as_slice()).map_err(invalid)?,
+            )
+            .into(),
+            KeyScheme::Secp256k1 => Secp256k1KeyPair::from(
+                Secp256k1PrivateKey::from_bytes(okm.as_slice()).map_err(invalid)?,
+            )
+            .into(),
+            KeyScheme::Secp256r1 => Secp256r1KeyPair::from(
+                Secp256r1PrivateKey::from_bytes(okm.as_slice()).map_err(invalid)?,
+            )
+            .into(),
+            KeyScheme::Bls12381 => BLS12381KeyPair::generate(&mut SeedRng(okm)).into(),
+        })
+    }
+
+    pub fn scheme(&self) -> KeyScheme {
+        match self {
+            EnclaveKeyPair::Ed25519(_) => KeyScheme::Ed25519,
+            EnclaveKeyPair::Secp256k1(_) => KeyScheme::Secp256k1,
+            EnclaveKeyPair::Secp256r1(_) => KeyScheme::Secp256r1,
+            EnclaveKeyPair::Bls12381(_) => KeyScheme::Bls12381,
+        }
+    }
+
+    /// Public key bytes: 32 bytes for Ed25519, 33 (compressed) for secp256k1 and secp256r1,
+    /// 96 (compressed G2 point) for BLS12-381.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match self {
+            EnclaveKeyPair::Ed25519(kp) => kp.public().as_bytes().to_vec(),
+            EnclaveKeyPair::Secp256k1(kp) => kp.public().as_bytes().to_vec(),
+            EnclaveKeyPair::Secp256r1(kp) => kp.public().as_bytes().to_vec(),
+            EnclaveKeyPair::Bls12381(kp) => kp.public().as_bytes().to_vec(),
+        }
+    }
+
+    /// Sui flagged public key: the scheme flag byte followed by [`Self::public_key_bytes`].
+    pub fn flagged_public_key(&self) -> Vec<u8> {
+        let mut flagged = vec![self.scheme() as u8];
+        flagged.extend_from_slice(&self.public_key_bytes());
+        flagged
+    }
+
+    /// The public key in the given textual `encoding`.
+    pub fn encode_public_key(&self, encoding: PublicKeyEncoding) -> String {
+        let public_key = self.public_key_bytes();
+        match encoding {
+            PublicKeyEncoding::Hex => Hex::encode(public_key),
+            PublicKeyEncoding::PrefixedHex => format!("0x{}", Hex::encode(public_key)),
+            PublicKeyEncoding::Base64 => Base64::encode(public_key),
+            PublicKeyEncoding::SuiBase64 => Base64::encode(self.flagged_public_key()),
+            PublicKeyEncoding::Multibase => self.multibase_public_key(),
+            PublicKeyEncoding::DidKey => format!("did:key:{}", self.multibase_public_key()),
+        }
+    }
+
+    fn multibase_public_key(&self) -> String {
+        let mut multicodec = self.scheme().multicodec_prefix().to_vec();
+        multicodec.extend_from_slice(&self.public_key_bytes());
+        format!("z{}", Base58::encode(multicodec))
+    }
+
+    /// Sui address owned by this key: Blake2b-256 of the flagged public key. Fails for
+    /// BLS12-381 keys, which cannot sign Sui transactions and so own no account.
+    pub fn sui_address(&self) -> NautilusResult<Address> {
+        if self.scheme() == KeyScheme::Bls12381 {
+            return Err(NautilusError::InvalidArgument(
+                "BLS12-381 keys have no Sui address".to_string(),
+            ));
+        }
+        Ok(Blake2b256::digest(self.flagged_public_key()).digest)
+    }
+
+    /// Sign `msg` with the scheme's default: Ed25519, ECDSA over SHA-256 (64 bytes `r || s`),
+    /// or BLS12-381 min-sig (48 bytes, a compressed G1 point).
+    pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            EnclaveKeyPair::Ed25519(kp) => kp.sign(msg).as_ref().to_vec(),
+            EnclaveKeyPair::Secp256k1(kp) => kp.sign(msg).as_ref().to_vec(),
+            EnclaveKeyPair::Secp256r1(kp) => kp.sign(msg).as_ref().to_vec(),
+            EnclaveKeyPair::Bls12381(kp) => kp.sign(msg).as_ref().to_vec(),
+        }
+    }
+
+    /// ECDSA-sign `msg` hashed with `hash`; `recoverable` appends the recovery id
+    /// (65 bytes `r || s || v`). With SHA-256, `v` is `0` or `1` as Sui expects; with
+    /// Keccak-256 it is `27` or `28` as EVM `ecrecover` expects, which only exists for
+    /// secp256k1. Fails for schemes other than ECDSA, and for recoverable Keccak-256
+    /// signatures with secp256r1 keys.
+    pub fn sign_ecdsa(
+        &self,
+        msg: &[u8],
+        hash: MessageHash,
+        recoverable: bool,
+    ) -> NautilusResult<Vec<u8>> {
+        match hash {
+            MessageHash::Sha256 => self.sign_ecdsa_with::<Sha256>(msg, recoverable),
+            MessageHash::Keccak256 => {
+                if recoverable && self.scheme() == KeyScheme::Secp256r1 {
+                    return Err(NautilusError::InvalidArgument(
+                        "EVM recoverable signatures require a Secp256k1 key".to_string(),
+                    ));
+                }
+                let mut signature = self.sign_ecdsa_with::<Keccak256>(msg, recoverable)?;
+                if recoverable {
+                    signature[64] += 27;
+                }
+                Ok(signature)
+            }
+        }
+    }
+
+    fn sign_ecdsa_with<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+        recoverable: bool,
+    ) -> NautilusResult<Vec<u8>> {
+        Ok(match (self, recoverable) {
+            (EnclaveKeyPair::Secp256k1(kp), false) => kp.sign_with_hash::<H>(msg).as_ref().to_vec(),
+            (EnclaveKeyPair::Secp256k1(kp), true) => {
+                kp.sign_recoverable_with_hash::<H>(msg).as_ref().to_vec()
+            }
+            (EnclaveKeyPair::Secp256r1(kp), false) => kp.sign_with_hash::<H>(msg).as_ref().to_vec(),
+            (EnclaveKeyPair::Secp256r1(kp), true) => {
+                kp.sign_recoverable_with_hash::<H>(msg).as_ref().to_vec()
+            }
+            (kp, _) => {
+                return Err(NautilusError::InvalidArgument(format!(
+                    "{:?} keys do not produce ECDSA signatures",
+                    kp.scheme()
+                )))
+            }
+        })
+    }
+}
+
+/// Verify a signature produced by [`EnclaveKeyPair::sign`] for a `scheme` public key.
+pub fn verify(
+    scheme: KeyScheme,
+    public_key: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> NautilusResult<()> {
+    match scheme {
+        KeyScheme::Ed25519 => {
+            verify_with::<Ed25519PublicKey, Ed25519Signature>(public_key, msg, signature)
+        }
+        KeyScheme::Secp256k1 => {
+            verify_with::<Secp256k1PublicKey, Secp256k1Signature>(public_key, msg, signature)
+        }
+        KeyScheme::Secp256r1 => {
+            verify_with::<Secp256r1PublicKey, Secp256r1Signature>(public_key, msg, signature)
+        }
+        KeyScheme::Bls12381 => {
+            verify_with::<BLS12381PublicKey, BLS12381Signature>(public_key, msg, signature)
+        }
+    }
+}
+
+fn verify_with<P, S>(public_key: &[u8], msg: &[u8], signature: &[u8]) -> NautilusResult<()>
+where
+    P: VerifyingKey<Sig = S>,
+    S: ToFromBytes,
+{
+    let invalid = |what: &str| NautilusError::InvalidSignature(what.to_string());
+    let public_key = P::from_bytes(public_key).map_err(|_| invalid("malformed public key"))?;
+    let signature = S::from_bytes(signature).map_err(|_| invalid("malformed signature"))?;
+    public_key
+        .verify(msg, &signature)
+        .map_err(|_| invalid("signature does not verify"))
+}
+
+/// Combine BLS12-381 signatures over the same message (e.g. one `IntentMessage` signed
+/// by several enclave replicas) into one 48-byte signature.
+pub fn aggregate_bls_signatures(signatures: &[&[u8]]) -> NautilusResult<Vec<u8>> {
+    if signatures.is_empty() {
+        return Err(NautilusError::InvalidArgument(
+            "no signatures to aggregate".to_string(),
+        ));
+    }
+    let signatures = signatures
+        .iter()
+        .map(|sig| {
+            BLS12381Signature::from_bytes(sig)
+                .map_err(|_| NautilusError::InvalidSignature("malformed signature".to_string()))
+        })
+        .collect::<NautilusResult<Vec<_>>>()?;
+    let aggregate = BLS12381AggregateSignature::aggregate(&signatures)
+        .map_err(|_| NautilusError::InvalidSignature("cannot aggregate".to_string()))?;
+    Ok(aggregate.as_ref().to_vec())
+}
+
+/// Verify an aggregate of [`aggregate_bls_signatures`] over `msg` by all `public_keys`.
+///
+/// BLS aggregation is only sound for keys known to be honestly generated: accept only
+/// public keys vouched for by an attestation document, never arbitrary caller keys.
+pub fn verify_bls_aggregate(
+    public_keys: &[&[u8]],
+    msg: &[u8],
+    aggregate: &[u8],
+) -> NautilusResult<()> {
+    let invalid = |what: &str| NautilusError::InvalidSignature(what.to_string());
+    if public_keys.is_empty() {
+        return Err(NautilusError::InvalidArgument("no public keys".to_string()));
+    }
+    let public_keys = public_keys
+        .iter()
+        .map(|pk| BLS12381PublicKey::from_bytes(pk).map_err(|_| invalid("malformed public key")))
+        .collect::<NautilusResult<Vec<_>>>()?;
+    let aggregate = BLS12381AggregateSignature::from_bytes(aggregate)
+        .map_err(|_| invalid("malformed signature"))?;
+    aggregate
+        .verify(&public_keys, msg)
+        .map_err(|_| invalid("signature does not verify"))
+}
+
+#[cfg(all(test, feature = "seeded-keys"))]
+mod tests {
+    use super::*;
+    use crate::{IntentMessage, IntentScope};
+    use fastcrypto::secp256k1::recoverable::Secp256k1RecoverableSignature;
+    use fastcrypto::traits::RecoverableSignature;
+    use serde::Serialize;
+
+    /// `SigningPayload` of `enclave::test_serde` in Move.
+    #[derive(Serialize, Clone)]
+    struct SigningPayload {
+        location: String,
+        temperature: u64,
+    }
+
+    /// Cross-language vector: the key derived from a fixed seed signs the intent
+    /// message of the Move `test_serde` test. Values were checked independently with
+    /// RFC 5869 HKDF and RFC 8032 Ed25519 (Python `cryptography`).
+    #[test]
+    fn seeded_ed25519_signs_move_test_serde_vector() {
+        let kp = EnclaveKeyPair::from_seed(KeyScheme::Ed25519, b"nautilus test vector").unwrap();
+        assert_eq!(
+            Hex::encode(kp.public_key_bytes()),
+            "8ede2742bdf7d445be013550bcecdae5eedd0b22d1b41f5870cd817a67ce2cca"
+        );
+
+        let msg = bcs::to_bytes(&IntentMessage {
+            intent: IntentScope::ProcessData,
+            timestamp_ms: 1744038900000,
+            data: SigningPayload {
+                location: "San Francisco".to_string(),
+                temperature: 13,
+            },
+        })
+        .unwrap();
+        assert_eq!(
+            Hex::encode(&msg),
+            "0020b1d110960100000d53616e204672616e636973636f0d00000000000000"
+        );
+
+        let signature = kp.sign(&msg);
+        assert_eq!(
+            Hex::encode(&signature),
+            "302d5b7dd775fb25b86179bfc1384f458d78e4e305d4d61afb3f0a956228b9ef\
+             81909d330aa1f07fa3d39ea389aac1067187e2119efd5e9d7729ed979a675800"
+        );
+        verify(KeyScheme::Ed25519, &kp.public_key_bytes(), &msg, &signature).unwrap();
+    }
+
+    /// Ethereum address of a secp256k1 key: the last 20 bytes of the Keccak-256 of
+    /// its uncompressed point without the `0x04` prefix.
+    fn ethereum_address(public_key: &Secp256k1PublicKey) -> Vec<u8> {
+        let point = public_key.pubkey.serialize_uncompressed();
+        Keccak256::digest(&point[1..]).digest[12..].to_vec()
+    }
+
+    /// Recoverable Keccak-256 signatures recover, as `ecrecover` would, to the signer.
+    #[test]
+    fn keccak_recoverable_signature_recovers_signer() {
+        let kp = EnclaveKeyPair::from_seed(KeyScheme::Secp256k1, b"nautilus test vector").unwrap();
+        let EnclaveKeyPair::Secp256k1(secp256k1) = &kp else {
+            unreachable!()
+        };
+        let msg = b"nautilus ecrecover";
+
+        let mut signature = kp.sign_ecdsa(msg, MessageHash::Keccak256, true).unwrap();
+        assert_eq!(signature.len(), 65);
+        assert!(matches!(signature[64], 27 | 28), "v = {}", signature[64]);
+
+        signature[64] -= 27;
+        let recovered = Secp256k1RecoverableSignature::from_bytes(&signature)
+            .unwrap()
+            .recover_with_hash::<Keccak256>(msg)
+            .unwrap();
+        assert_eq!(&recovered, secp256k1.public());
+        assert_eq!(
+            ethereum_address(&recovered),
+            ethereum_address(secp256k1.public())
+        );
+    }
+
+    #[test]
+    fn keccak_recoverable_rejects_secp256r1() {
+        let kp = EnclaveKeyPair::from_seed(KeyScheme::Secp256r1, b"nautilus test vector").unwrap();
+        assert!(matches!(
+            kp.sign_ecdsa(b"msg", MessageHash::Keccak256, true),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+        let signature = kp.sign_ecdsa(b"msg", MessageHash::Sha256, true).unwrap();
+        assert!(signature[64] <= 1);
+    }
+
+    fn bls_replicas() -> Vec<EnclaveKeyPair> {
+        (1..=3u8)
+            .map(|i| EnclaveKeyPair::from_seed(KeyScheme::Bls12381, &[b'r', i]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn bls_aggregate_of_all_replicas_verifies() {
+        let replicas = bls_replicas();
+        let public_keys: Vec<_> = replicas.iter().map(|kp| kp.public_key_bytes()).collect();
+        let public_keys: Vec<&[u8]> = public_keys.iter().map(Vec::as_slice).collect();
+        let signatures: Vec<_> = replicas.iter().map(|kp| kp.sign(b"replicated")).collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(Vec::as_slice).collect();
+
+        let aggregate = aggregate_bls_signatures(&signatures).unwrap();
+        assert_eq!(aggregate.len(), 48);
+        verify_bls_aggregate(&public_keys, b"replicated", &aggregate).unwrap();
+
+        let invalid = |result| matches!(result, Err(NautilusError::InvalidSignature(_)));
+        assert!(invalid(verify_bls_aggregate(
+            &public_keys,
+            b"other",
+            &aggregate
+        )));
+        assert!(invalid(verify_bls_aggregate(
+            &public_keys[..2],
+            b"replicated",
+            &aggregate
+        )));
+
+        let other_msg = replicas[2].sign(b"other");
+        let mixed = aggregate_bls_signatures(&[signatures[0], signatures[1], &other_msg]).unwrap();
+        assert!(invalid(verify_bls_aggregate(
+            &public_keys,
+            b"replicated",
+            &mixed
+        )));
+
+        let outsider = EnclaveKeyPair::from_seed(KeyScheme::Bls12381, b"outsider").unwrap();
+        let outsider_sig = outsider.sign(b"replicated");
+        let forged =
+            aggregate_bls_signatures(&[signatures[0], signatures[1], &outsider_sig]).unwrap();
+        assert!(invalid(verify_bls_aggregate(
+            &public_keys,
+            b"replicated",
+            &forged
+        )));
+    }
+
+    #[test]
+    fn bls_aggregate_rejects_empty_input() {
+        let kp = &bls_replicas()[0];
+        let signature = kp.sign(b"replicated");
+        assert!(matches!(
+            aggregate_bls_signatures(&[]),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            verify_bls_aggregate(&[], b"replicated", &signature),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    /// Address computed independently as Blake2b-256 of `0x00 || public key`
+    /// (Python `hashlib`).
+    #[test]
+    fn sui_address_of_seeded_keys() {
+        let kp = EnclaveKeyPair::from_seed(KeyScheme::Ed25519, b"nautilus test vector").unwrap();
+        assert_eq!(
+            Hex::encode(kp.sui_address().unwrap()),
+            "32615d8b03e965ad5e51b61e50aff4916912c5334669bd674f1442450932bb92"
+        );
+        let bls = EnclaveKeyPair::from_seed(KeyScheme::Bls12381, b"nautilus test vector").unwrap();
+        assert!(matches!(
+            bls.sui_address(),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    /// Seeded BLS12-381 keys are `KeyGen(HKDF output)`, as with blst or py_ecc.
+    #[test]
+    fn seeded_bls12381_public_key() {
+        let kp = EnclaveKeyPair::from_seed(KeyScheme::Bls12381, b"nautilus test vector").unwrap();
+        assert_eq!(
+            Hex::encode(kp.public_key_bytes()),
+            "b217387351043b467e4c289297c841165db217f2e7f85bfc83b95ca80dcbeeb8\
+             585fe4f999a739f9edbe9e31bea429850561581e146a0ce7a1afd97fa0bfe3aaf\
+             073d73d46e8477d2009b0954d24d4ba7bea907163b894cf0b4070bf7172eff5"
+        );
+    }
+}
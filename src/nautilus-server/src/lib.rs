@@ -4,103 +4,778 @@
 //! Nautilus FFI library
 //!
 //! Minimal, general-purpose FFI for hosts like Bun/JS:
-//! - Generate ephemeral Ed25519 keypair.
-//! - Get public key (hex).
-//! - Get Nitro Enclave attestation bound to public key.
+//! - Generate ephemeral Ed25519, secp256k1, secp256r1 or BLS12-381 keypairs, seeded
+//!   from NSM entropy (see [`keys`] and [`rng`]), verify their signatures, and
+//!   aggregate BLS signatures from several enclave replicas.
+//! - Get public key (hex, base64, Sui flagged base64, multibase or did:key), and the
+//!   key's Sui address.
+//! - Get Nitro Enclave attestation bound to public key (optionally nonce and user data).
 //! - Sign arbitrary bytes as IntentMessage, returning JSON or BCS+signature.
+//! - Reuse attestation documents per keypair for a configurable TTL (see [`attestation_cache`]).
+//! - Attest a bundle of public keys (signing, encryption, session) in one document
+//!   (see [`key_bundle`]).
+//! - Decode an attestation document into JSON.
+//! - Describe, extend and lock PCRs (e.g. to measure runtime configuration).
+//! - Describe the NSM module and collect diagnostics for health checks.
+//! - Build the Sui `register_enclave` transaction offline (see [`sui_tx`]).
+//! - Rotate a keypair, with the old key signing a handover to the attested new one
+//!   (see [`rotation`]).
+//! - Compose a Sui multisig over replica keys and combine their signatures into a
+//!   k-of-n multisig signature (see [`multisig`]).
+//!
+//! Rust consumers can also verify attestation documents offline with [`verify`],
+//! and predict the PCRs of an enclave image with [`eif`] (see the `eif_pcrs` binary).
+//! With the `mock-nsm` feature, NSM requests are served by [`mock_nsm`] so the
+//! whole flow runs outside a Nitro Enclave. With the `seeded-keys` feature,
+//! `nautilus_generate_keypair_from_seed` derives reproducible keypairs for test vectors.
 //!
 //! Usage order and memory:
-//! 1) `nautilus_generate_ed25519_keypair` → keypair pointer
-//! 2) `nautilus_get_public_key_hex` / `nautilus_get_attestation` (optional)
+//! 1) `nautilus_generate_keypair` / `nautilus_generate_ed25519_keypair` /
+//!    `nautilus_generate_named_keypair` → keypair handle
+//! 2) `nautilus_get_public_key_hex` / `nautilus_get_attestation` /
+//!    `nautilus_get_attestation_with_data` (optional)
 //! 3) `nautilus_sign_intent_message_json` or `nautilus_sign_intent_message_bcs`
-//! 4) `nautilus_free_cstr` on any returned C string exactly once
-//! 5) `nautilus_free_keypair` exactly once at the end
+//! 4) `nautilus_free_cstr` on any returned C string exactly once, and
+//!    `nautilus_free_buffer` on any returned byte buffer exactly once
+//! 5) `nautilus_free_keypair` once per handle at the end, or
+//!    `nautilus_destroy_all_keys` at shutdown
+//!
+//! Errors:
+//! - Functions returning a pointer return NULL on failure.
+//! - Every call records a status code, readable with `nautilus_last_error_code`
+//!   (`0` = success) and `nautilus_last_error_message` on the same thread.
+//! - Panics never unwind into the host; they are caught and reported as errors.
 //!
 //! Safety:
 //! - Pointers and lengths must be valid; otherwise undefined behavior.
 //! - Returned C strings are owned by Rust; free via `nautilus_free_cstr` once.
+//! - Returned `FfiBuffer`s (`*_bytes` variants, raw binary instead of hex) are
+//!   owned by Rust; free via `nautilus_free_buffer` once.
 //! - Do not double-free; do not free with other functions.
-//! - The keypair pointer is opaque; only use with these FFI functions.
-use fastcrypto::ed25519::Ed25519KeyPair;
-use fastcrypto::encoding::{Encoding, Hex};
-use fastcrypto::traits::{KeyPair, Signer, ToFromBytes};
+//! - Keypairs are referenced by opaque integer handles into a registry (see
+//!   [`key_registry`]); unknown or freed handles fail with `InvalidHandle`
+//!   rather than touching freed memory. `0` is never a valid handle.
+//! - The registry's copy of each private key is locked into RAM where the platform
+//!   allows and wiped when freed; stack temporaries from key generation are not
+//!   (see [`secure_box`]).
+
+// FFI exports take raw pointers from the host by design; validity is a documented precondition.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use fastcrypto::encoding::{Base64, Encoding, Hex};
+use key_registry::{KeyHandle, INVALID_HANDLE};
+use keys::EnclaveKeyPair;
+use secure_box::SecureBox;
 use serde::{Deserialize, Serialize};
 use std::ffi::{c_char, CString};
+use std::sync::Arc;
+
+pub mod attestation;
+pub mod attestation_cache;
+pub mod eif;
+pub mod error;
+pub mod key_bundle;
+pub mod key_registry;
+pub mod keys;
+#[cfg(feature = "mock-nsm")]
+pub mod mock_nsm;
+pub mod multisig;
+pub mod nsm;
+pub mod rng;
+pub mod rotation;
+pub mod secure_box;
+pub mod sui_tx;
+pub mod verify;
 
-// FFI-only crate: application modules removed.
+pub use error::{NautilusError, NautilusResult, NAUTILUS_OK};
 
+/// Owned byte buffer returned to the host: `len` bytes at `ptr`, allocated with
+/// capacity `cap`. Free with `nautilus_free_buffer` exactly once.
 #[repr(C)]
-pub struct FfiKeyPair {
-    inner: Ed25519KeyPair,
+pub struct FfiBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub cap: usize,
+}
+
+impl FfiBuffer {
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = std::mem::ManuallyDrop::new(bytes);
+        FfiBuffer {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            cap: bytes.capacity(),
+        }
+    }
+}
+
+/// Generate a new ephemeral Ed25519 keypair and return its handle.
+/// Same as `nautilus_generate_keypair(0)`.
+#[no_mangle]
+pub extern "C" fn nautilus_generate_ed25519_keypair() -> KeyHandle {
+    nautilus_generate_keypair(keys::KeyScheme::Ed25519 as u8)
+}
+
+/// Generate a new ephemeral keypair of the given scheme (Sui flag:
+/// `0` = Ed25519, `1` = secp256k1, `2` = secp256r1, `4` = BLS12-381 min-sig) and
+/// return its handle.
+/// Entropy comes from the NSM according to the RNG policy (see `nautilus_set_rng_policy`).
+/// Caller must call `nautilus_free_keypair(handle)` once to release the key.
+/// On error (unknown scheme, NSM unavailable under a strict policy), returns `0`.
+#[no_mangle]
+pub extern "C" fn nautilus_generate_keypair(scheme: u8) -> KeyHandle {
+    error::ffi_call(INVALID_HANDLE, || generate_keypair(scheme, None))
+}
+
+/// Same as `nautilus_generate_keypair`, but also registers the key under `name`
+/// (UTF-8, non-empty, not already in use) for `nautilus_get_keypair_by_name`.
+/// Returns `0` on error.
+///
+/// Safety: `name_ptr` must point to `name_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_generate_named_keypair(
+    scheme: u8,
+    name_ptr: *const u8,
+    name_len: usize,
+) -> KeyHandle {
+    error::ffi_call(INVALID_HANDLE, || {
+        generate_keypair(scheme, Some(utf8(name_ptr, name_len, "name")?))
+    })
+}
+
+fn generate_keypair(scheme: u8, name: Option<&str>) -> NautilusResult<KeyHandle> {
+    let scheme = keys::KeyScheme::try_from(scheme)?;
+    let kp = EnclaveKeyPair::generate(scheme, &mut *rng::key_rng()?);
+    key_registry::with_registry(|registry| registry.insert(kp, name))
+}
+
+/// Return the handle of the keypair registered under `name`, or `0` if there is none.
+///
+/// Safety: `name_ptr` must point to `name_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_get_keypair_by_name(name_ptr: *const u8, name_len: usize) -> KeyHandle {
+    error::ffi_call(INVALID_HANDLE, || {
+        let name = utf8(name_ptr, name_len, "name")?;
+        key_registry::with_registry(|registry| registry.handle_by_name(name))
+    })
+}
+
+/// Derive a keypair of the given scheme deterministically from `seed_len` bytes at
+/// `seed_ptr` (`seeded-keys` feature only; see `keys::EnclaveKeyPair::from_seed`), so
+/// signed outputs can be snapshotted and compared across languages.
+/// Never use for enclave keys: anyone with the seed can sign as the enclave.
+/// Caller must call `nautilus_free_keypair(handle)` once to release the key.
+/// On error (unknown scheme, empty seed), returns `0`.
+///
+/// Safety: `seed_ptr` must point to `seed_len` bytes.
+#[cfg(feature = "seeded-keys")]
+#[no_mangle]
+pub extern "C" fn nautilus_generate_keypair_from_seed(
+    scheme: u8,
+    seed_ptr: *const u8,
+    seed_len: usize,
+) -> KeyHandle {
+    error::ffi_call(INVALID_HANDLE, || {
+        let scheme = keys::KeyScheme::try_from(scheme)?;
+        let kp = EnclaveKeyPair::from_seed(scheme, bytes(seed_ptr, seed_len, "seed")?)?;
+        key_registry::with_registry(|registry| registry.insert(kp, None))
+    })
+}
+
+/// Close the shared NSM device handle (e.g. on SIGTERM). Later NSM calls reopen it.
+#[no_mangle]
+pub extern "C" fn nautilus_nsm_close() {
+    error::ffi_call((), || {
+        nsm::close_session();
+        Ok(())
+    })
+}
+
+/// Set the entropy policy for key generation:
+/// `0` = NSM mixed with OS RNG, strict (default); `1` = NSM only, strict;
+/// `2` = NSM mixed with OS RNG, falling back to OS RNG if the NSM is unavailable;
+/// `3` = OS RNG only.
+/// Returns `0` on success or an error code for an unknown policy.
+#[no_mangle]
+pub extern "C" fn nautilus_set_rng_policy(policy: u8) -> i32 {
+    ffi_status(|| {
+        rng::set_policy(rng::RngPolicy::try_from(policy)?);
+        Ok(())
+    })
+}
+
+/// Report the entropy source used for the most recently generated key:
+/// `0` = none yet, `1` = NSM mixed with OS RNG, `2` = NSM only,
+/// `3` = OS RNG after NSM fallback, `4` = OS RNG by policy.
+#[no_mangle]
+pub extern "C" fn nautilus_last_entropy_source() -> u8 {
+    error::ffi_call(rng::EntropySource::None as u8, || {
+        Ok(rng::last_entropy_source() as u8)
+    })
+}
+
+/// Release a keypair handle (and its name, if any) and drop its cached attestations.
+/// Returns `0` on success, or `InvalidHandle` for a handle that was never issued or
+/// was already freed; the handle is never valid again either way.
+#[no_mangle]
+pub extern "C" fn nautilus_free_keypair(handle: KeyHandle) -> i32 {
+    ffi_status(|| {
+        let kp = key_registry::with_registry(|registry| registry.remove(handle))?;
+        attestation_cache::with_cache(|cache| cache.invalidate(&kp.public_key_bytes()));
+        Ok(())
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyRotationJson {
+    /// Handle of the new keypair.
+    pub handle: KeyHandle,
+    /// Hex public keys.
+    pub old_public_key: String,
+    pub new_public_key: String,
+    pub timestamp_ms: u64,
+    pub reason: String,
+    /// Hex BCS `IntentMessage<KeyHandover>` signed by the old key.
+    pub handover_bcs: String,
+    /// Hex signature of the old key over `handover_bcs`.
+    pub signature: String,
+    /// Hex attestation document for the new key, with the Blake2b-256 digest of
+    /// `handover_bcs` as `user_data`.
+    pub attestation: String,
 }
 
-/// Generate a new ephemeral Ed25519 keypair and return an opaque pointer.
-/// Caller must call `nautilus_free_keypair(ptr)` once to release memory.
+/// Rotate a keypair: generate a new key of the same scheme, have the old key sign a
+/// handover (old public key, new public key, `timestamp_ms`, `reason`) under the
+/// `KeyRotation` intent scope (`1`), and attest the new key bound to that handover.
+/// If the old key has a name, the name moves to the new key. The old handle stays
+/// valid (e.g. for in-flight requests) until freed with `nautilus_free_keypair`.
+/// Returns JSON: `{ handle, old_public_key, new_public_key, timestamp_ms, reason,
+/// handover_bcs, signature, attestation }`.
+/// Caller must free the returned C string via `nautilus_free_cstr`. On error, returns NULL
+/// and no key is created.
+///
+/// Parameters:
+/// - `reason_ptr` / `reason_len`: UTF-8 reason; may be empty.
+/// - `nonce_ptr` / `nonce_len`: nonce for the new key's attestation; zero length omits it.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
 #[no_mangle]
-pub extern "C" fn nautilus_generate_ed25519_keypair() -> *mut FfiKeyPair {
-    let kp = Ed25519KeyPair::generate(&mut rand::thread_rng());
-    Box::into_raw(Box::new(FfiKeyPair { inner: kp }))
+pub extern "C" fn nautilus_rotate_keypair(
+    handle: KeyHandle,
+    timestamp_ms: u64,
+    reason_ptr: *const u8,
+    reason_len: usize,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let old = keypair(handle)?;
+        let reason = utf8(reason_ptr, reason_len, "reason")?;
+        let nonce = optional_field(bytes(nonce_ptr, nonce_len, "nonce")?);
+        let new = EnclaveKeyPair::generate(old.scheme(), &mut *rng::key_rng()?);
+        let new_public_key = new.public_key_bytes();
+        let handover = rotation::sign_handover(&old, &new_public_key, timestamp_ms, reason)?;
+        let user_data = serde_bytes::ByteBuf::from(handover.digest().to_vec());
+        let attestation = nsm::request_attestation(&new_public_key, Some(user_data), nonce)?;
+        let new_handle =
+            key_registry::with_registry(|registry| registry.insert_rotated(new, handle))?;
+        to_json(&KeyRotationJson {
+            handle: new_handle,
+            old_public_key: Hex::encode(handover.message.data.old_public_key),
+            new_public_key: Hex::encode(new_public_key),
+            timestamp_ms,
+            reason: handover.message.data.reason,
+            handover_bcs: Hex::encode(handover.bcs),
+            signature: Hex::encode(handover.signature),
+            attestation: Hex::encode(attestation),
+        })
+    })
 }
 
-/// Free a previously returned keypair pointer.
-/// Safe to call with NULL pointer (no-op). Do not double-free.
+/// Destroy every keypair (e.g. on SIGTERM) and clear the attestation cache.
+/// All handles become invalid. The registry's key memory is wiped as soon as no call
+/// is using it, i.e. immediately unless another thread is mid-call. Returns `0`.
 #[no_mangle]
-pub extern "C" fn nautilus_free_keypair(ptr: *mut FfiKeyPair) {
-    if !ptr.is_null() {
-        unsafe { Box::from_raw(ptr) };
+pub extern "C" fn nautilus_destroy_all_keys() -> i32 {
+    ffi_status(|| {
+        drop(key_registry::with_registry(|registry| registry.clear()));
+        attestation_cache::with_cache(|cache| cache.clear());
+        Ok(())
+    })
+}
+
+/// Look up the keypair behind a handle, rejecting unknown and freed handles.
+fn keypair(handle: KeyHandle) -> NautilusResult<Arc<SecureBox<EnclaveKeyPair>>> {
+    key_registry::with_registry(|registry| registry.get(handle))
+}
+
+/// Borrow a caller buffer, rejecting a NULL pointer with a non-zero length.
+fn bytes<'a>(ptr: *const u8, len: usize, name: &'static str) -> NautilusResult<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(NautilusError::InvalidPointer(name));
     }
+    Ok(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+/// Borrow a caller buffer as UTF-8 text.
+fn utf8<'a>(ptr: *const u8, len: usize, name: &'static str) -> NautilusResult<&'a str> {
+    std::str::from_utf8(bytes(ptr, len, name)?)
+        .map_err(|_| NautilusError::InvalidArgument(format!("{name} is not valid UTF-8")))
 }
 
 /// Convert a Rust `String` into a raw C string (caller must free).
-fn to_cstr(s: String) -> *mut c_char {
-    CString::new(s).unwrap().into_raw()
+fn to_cstr(s: String) -> NautilusResult<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|e| NautilusError::Serialization(e.to_string()))
+}
+
+/// Run a string-returning export body and convert the result for the host.
+/// Returns NULL on failure or panic.
+fn ffi_cstr(f: impl FnOnce() -> NautilusResult<String>) -> *mut c_char {
+    error::ffi_call(std::ptr::null_mut(), || f().and_then(to_cstr))
+}
+
+/// Run a status-returning export body; returns the recorded status code (`0` on success).
+fn ffi_status(f: impl FnOnce() -> NautilusResult<()>) -> i32 {
+    error::ffi_call((), f);
+    error::nautilus_last_error_code()
+}
+
+/// Run a bytes-returning export body and box the result for the host.
+/// Returns NULL on failure or panic.
+fn ffi_buffer(f: impl FnOnce() -> NautilusResult<Vec<u8>>) -> *mut FfiBuffer {
+    error::ffi_call(std::ptr::null_mut(), || {
+        f().map(|bytes| Box::into_raw(Box::new(FfiBuffer::from_vec(bytes))))
+    })
+}
+
+/// Free a byte buffer previously returned by this library.
+/// Safe to call with NULL pointer (no-op). Only free once per returned buffer.
+#[no_mangle]
+pub extern "C" fn nautilus_free_buffer(buf: *mut FfiBuffer) {
+    error::ffi_call((), || {
+        if !buf.is_null() {
+            unsafe {
+                let buf = Box::from_raw(buf);
+                drop(Vec::from_raw_parts(buf.ptr, buf.len, buf.cap));
+            }
+        }
+        Ok(())
+    })
 }
 
 /// Free a C string previously returned by this library.
 /// Safe to call with NULL pointer (no-op). Only free once per returned string.
 #[no_mangle]
 pub extern "C" fn nautilus_free_cstr(s: *mut c_char) {
-    if !s.is_null() {
-        unsafe { let _ = CString::from_raw(s); }
-    }
+    error::ffi_call((), || {
+        if !s.is_null() {
+            unsafe {
+                let _ = CString::from_raw(s);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Return the hex-encoded public key for the given keypair handle
+/// (32 bytes for Ed25519, 33 compressed bytes for secp256k1 and secp256r1, 96 bytes for BLS12-381).
+/// Returns a newly allocated C string; caller must free via `nautilus_free_cstr`.
+/// Returns NULL for an unknown or freed handle.
+#[no_mangle]
+pub extern "C" fn nautilus_get_public_key_hex(handle: KeyHandle) -> *mut c_char {
+    ffi_cstr(|| keypair(handle).map(|kp| Hex::encode(kp.public_key_bytes())))
 }
 
-/// Return the hex-encoded Ed25519 public key for the given keypair pointer.
+/// Return the public key of the given keypair handle in the chosen `encoding`:
+/// `0` = hex, `1` = `0x`-prefixed hex, `2` = base64, `3` = Sui flagged base64
+/// (as `nautilus_get_sui_public_key`), `4` = multibase base58btc of the multicodec
+/// key (`z...`), `5` = `did:key:z...`.
 /// Returns a newly allocated C string; caller must free via `nautilus_free_cstr`.
+/// Returns NULL for an unknown or freed handle, or an unknown encoding.
 #[no_mangle]
-pub extern "C" fn nautilus_get_public_key_hex(ptr: *mut FfiKeyPair) -> *mut c_char {
-    let pk = unsafe { (&*ptr).inner.public() };
-    to_cstr(Hex::encode(pk.as_bytes()))
+pub extern "C" fn nautilus_get_public_key(handle: KeyHandle, encoding: u8) -> *mut c_char {
+    ffi_cstr(|| {
+        let encoding = keys::PublicKeyEncoding::try_from(encoding)?;
+        keypair(handle).map(|kp| kp.encode_public_key(encoding))
+    })
+}
+
+/// Return the raw public key bytes for the given keypair handle.
+/// Free the returned buffer via `nautilus_free_buffer`. Returns NULL for an unknown or freed handle.
+#[no_mangle]
+pub extern "C" fn nautilus_get_public_key_bytes(handle: KeyHandle) -> *mut FfiBuffer {
+    ffi_buffer(|| keypair(handle).map(|kp| kp.public_key_bytes()))
+}
+
+/// Return the Sui address owned by the keypair (Blake2b-256 of the scheme flag byte
+/// followed by the public key) as `0x`-prefixed hex, e.g. to send it objects.
+/// Returns a newly allocated C string; caller must free via `nautilus_free_cstr`.
+/// Returns NULL for an unknown or freed handle, or a BLS12-381 keypair (which cannot
+/// sign Sui transactions, so its address would not be a usable account).
+#[no_mangle]
+pub extern "C" fn nautilus_get_sui_address(handle: KeyHandle) -> *mut c_char {
+    ffi_cstr(|| {
+        Ok(format!(
+            "0x{}",
+            Hex::encode(keypair(handle)?.sui_address()?)
+        ))
+    })
+}
+
+/// Return the flagged public key (scheme flag byte followed by the public key) in
+/// base64, the Sui format accepted by e.g. `sui keytool` and multisig configs.
+/// Returns a newly allocated C string; caller must free via `nautilus_free_cstr`.
+/// Returns NULL for an unknown or freed handle.
+#[no_mangle]
+pub extern "C" fn nautilus_get_sui_public_key(handle: KeyHandle) -> *mut c_char {
+    ffi_cstr(|| keypair(handle).map(|kp| kp.encode_public_key(keys::PublicKeyEncoding::SuiBase64)))
+}
+
+/// Wrap non-empty caller bytes for an optional NSM request field.
+fn optional_field(bytes: &[u8]) -> Option<serde_bytes::ByteBuf> {
+    (!bytes.is_empty()).then(|| serde_bytes::ByteBuf::from(bytes.to_vec()))
 }
 
 /// Request a Nitro Enclave attestation document committed to the keypair public key.
 /// Returns the attestation document as hex (newly allocated C string; free with `nautilus_free_cstr`).
-/// On error, returns an empty string.
-#[no_mangle]
-pub extern "C" fn nautilus_get_attestation(ptr: *mut FfiKeyPair) -> *mut c_char {
-    let pk = unsafe { (&*ptr).inner.public() };
-    let fd = nsm_api::driver::nsm_init();
-    let request = nsm_api::api::Request::Attestation {
-        user_data: None,
-        nonce: None,
-        public_key: Some(serde_bytes::ByteBuf::from(pk.as_bytes().to_vec())),
+/// On error, returns NULL; outside an enclave the error code is `NsmUnavailable`.
+#[no_mangle]
+pub extern "C" fn nautilus_get_attestation(handle: KeyHandle) -> *mut c_char {
+    nautilus_get_attestation_with_data(handle, std::ptr::null(), 0, std::ptr::null(), 0)
+}
+
+/// Request a Nitro Enclave attestation document committed to the keypair public key,
+/// a caller-supplied `nonce` (challenge-response freshness) and `user_data`
+/// (application metadata such as a config hash or Sui package ID).
+/// Returns the attestation document as hex (newly allocated C string; free with `nautilus_free_cstr`).
+/// On error, returns NULL; outside an enclave the error code is `NsmUnavailable`.
+///
+/// Parameters:
+/// - `handle`: keypair handle from `nautilus_generate_keypair`.
+/// - `nonce_ptr` / `nonce_len`: nonce bytes; zero length omits the field.
+/// - `user_data_ptr` / `user_data_len`: user data bytes; zero length omits the field.
+///
+/// The NSM limits `nonce` and `user_data` to 512 bytes each.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_get_attestation_with_data(
+    handle: KeyHandle,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+    user_data_ptr: *const u8,
+    user_data_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        attestation(handle, nonce_ptr, nonce_len, user_data_ptr, user_data_len).map(Hex::encode)
+    })
+}
+
+/// Same as `nautilus_get_attestation_with_data`, but returns the raw (binary)
+/// attestation document. Free the returned buffer via `nautilus_free_buffer`.
+/// On error, returns NULL.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_get_attestation_bytes(
+    handle: KeyHandle,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+    user_data_ptr: *const u8,
+    user_data_len: usize,
+) -> *mut FfiBuffer {
+    ffi_buffer(|| attestation(handle, nonce_ptr, nonce_len, user_data_ptr, user_data_len))
+}
+
+fn attestation(
+    handle: KeyHandle,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+    user_data_ptr: *const u8,
+    user_data_len: usize,
+) -> NautilusResult<Vec<u8>> {
+    let kp = keypair(handle)?;
+    let nonce = optional_field(bytes(nonce_ptr, nonce_len, "nonce")?);
+    let user_data = optional_field(bytes(user_data_ptr, user_data_len, "user_data")?);
+    nsm::request_attestation(&kp.public_key_bytes(), user_data, nonce)
+}
+
+/// Like `nautilus_get_attestation_with_data`, but reuses the last document for this
+/// keypair while it is younger than the cache TTL and was requested with the same
+/// `user_data`. A non-empty `nonce` always bypasses the cache and yields a fresh document.
+/// Returns hex (free with `nautilus_free_cstr`), or NULL on error.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_get_cached_attestation(
+    handle: KeyHandle,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+    user_data_ptr: *const u8,
+    user_data_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let kp = keypair(handle)?;
+        let nonce = bytes(nonce_ptr, nonce_len, "nonce")?;
+        let user_data = bytes(user_data_ptr, user_data_len, "user_data")?;
+        let document = attestation_cache::get(
+            &kp.public_key_bytes(),
+            (!user_data.is_empty()).then_some(user_data),
+            (!nonce.is_empty()).then_some(nonce),
+        )?;
+        Ok(Hex::encode(document))
+    })
+}
+
+/// Request a new attestation document for this keypair and `user_data` now,
+/// replacing the cached one regardless of its age.
+/// Returns hex (free with `nautilus_free_cstr`), or NULL on error.
+///
+/// Safety: `user_data_ptr` must point to `user_data_len` bytes (zero length omits the field).
+#[no_mangle]
+pub extern "C" fn nautilus_refresh_attestation(
+    handle: KeyHandle,
+    user_data_ptr: *const u8,
+    user_data_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let kp = keypair(handle)?;
+        let user_data = bytes(user_data_ptr, user_data_len, "user_data")?;
+        let document = attestation_cache::refresh(
+            &kp.public_key_bytes(),
+            (!user_data.is_empty()).then_some(user_data),
+        )?;
+        Ok(Hex::encode(document))
+    })
+}
+
+/// Set how long cached attestation documents are reused, in milliseconds
+/// (default 60 000). `0` disables caching. Returns `0`.
+#[no_mangle]
+pub extern "C" fn nautilus_set_attestation_cache_ttl_ms(ttl_ms: u64) -> i32 {
+    ffi_status(|| {
+        attestation_cache::with_cache(|cache| {
+            cache.set_ttl(std::time::Duration::from_millis(ttl_ms))
+        });
+        Ok(())
+    })
+}
+
+/// Decode a raw (binary) attestation document into JSON with hex-encoded byte fields:
+/// `{ module_id, digest, timestamp, pcrs: { <index>: <hex> }, certificate, cabundle: [..],
+///    public_key, user_data, nonce }` (optional fields are `null` when absent).
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (malformed COSE or CBOR), returns NULL.
+///
+/// This only decodes the document; it does not verify signatures or certificates.
+///
+/// Safety: `doc_ptr` must point to `doc_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_parse_attestation(doc_ptr: *const u8, doc_len: usize) -> *mut c_char {
+    ffi_cstr(|| {
+        let doc = attestation::parse_attestation(bytes(doc_ptr, doc_len, "document")?)?;
+        to_json(&attestation::AttestationDocumentJson::from(&doc))
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyBundleAttestationJson {
+    /// Hex attestation document.
+    pub attestation: String,
+    /// Hex BCS-encoded key bundle whose digest is the document's `user_data`.
+    pub bundle: String,
+}
+
+/// Request an attestation document vouching for the keypair's signing key plus
+/// additional public keys, given as JSON:
+/// `[{ "purpose": "encryption" | "session", "public_key": <hex> }]`.
+/// The document commits to the signing key as `public_key` and to the Blake2b-256
+/// digest of the BCS key bundle as `user_data`; an optional `nonce` is passed through.
+/// Returns JSON: `{ attestation: <hex>, bundle: <hex BCS> }`.
+/// Caller must free the returned C string via `nautilus_free_cstr`. On error, returns NULL.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_attest_key_bundle(
+    handle: KeyHandle,
+    keys_json_ptr: *const u8,
+    keys_json_len: usize,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| attest_key_bundle(handle, keys_json_ptr, keys_json_len, nonce_ptr, nonce_len))
+}
+
+fn attest_key_bundle(
+    handle: KeyHandle,
+    keys_json_ptr: *const u8,
+    keys_json_len: usize,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+) -> NautilusResult<String> {
+    let kp = keypair(handle)?;
+    let keys_json = bytes(keys_json_ptr, keys_json_len, "keys_json")?;
+    let additional = if keys_json.is_empty() {
+        Vec::new()
+    } else {
+        key_bundle::KeyBundle::additional_keys_from_json(keys_json)?
     };
-    let response = nsm_api::driver::nsm_process_request(fd, request);
-    match response {
-        nsm_api::api::Response::Attestation { document } => {
-            nsm_api::driver::nsm_exit(fd);
-            to_cstr(Hex::encode(document))
-        }
-        _ => {
-            nsm_api::driver::nsm_exit(fd);
-            to_cstr(String::new())
+    let bundle = key_bundle::KeyBundle::new(&kp.public_key_bytes(), additional);
+    let nonce = optional_field(bytes(nonce_ptr, nonce_len, "nonce")?);
+    let user_data = serde_bytes::ByteBuf::from(bundle.digest()?.to_vec());
+    let document = nsm::request_attestation(&kp.public_key_bytes(), Some(user_data), nonce)?;
+    to_json(&KeyBundleAttestationJson {
+        attestation: Hex::encode(document),
+        bundle: Hex::encode(bundle.to_bcs()?),
+    })
+}
+
+/// Decode a BCS key bundle into JSON:
+/// `{ version, keys: [{ purpose, public_key: <hex> }], digest: <hex> }`.
+/// If `doc_len` is non-zero, also checks that the raw attestation document at `doc_ptr`
+/// commits to the bundle (`user_data` = digest, `public_key` = signing key) and fails
+/// with `InvalidAttestation` otherwise. The document itself is not verified.
+/// Caller must free the returned C string via `nautilus_free_cstr`. On error, returns NULL.
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_parse_key_bundle(
+    bundle_ptr: *const u8,
+    bundle_len: usize,
+    doc_ptr: *const u8,
+    doc_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let bundle = key_bundle::KeyBundle::from_bcs(bytes(bundle_ptr, bundle_len, "bundle")?)?;
+        let document = bytes(doc_ptr, doc_len, "document")?;
+        if !document.is_empty() {
+            bundle.check_document(&attestation::parse_attestation(document)?)?;
         }
-    }
+        to_json(&bundle.to_json()?)
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PcrDescriptionJson {
+    pub index: u16,
+    pub locked: bool,
+    pub value: String,
+}
+
+/// Describe PCR `index`.
+/// Returns JSON: `{ index, locked, value: <hex> }`.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (NSM unavailable, invalid index), returns NULL.
+#[no_mangle]
+pub extern "C" fn nautilus_describe_pcr(index: u16) -> *mut c_char {
+    ffi_cstr(|| {
+        let pcr = nsm::describe_pcr(index)?;
+        to_json(&PcrDescriptionJson {
+            index,
+            locked: pcr.locked,
+            value: Hex::encode(pcr.value),
+        })
+    })
+}
+
+/// Extend PCR `index` with the given bytes and return the new PCR value as hex.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (NSM unavailable, invalid or locked index), returns NULL.
+///
+/// Use PCR16 or above for application measurements; PCR0-15 are locked at boot.
+///
+/// Safety: `data_ptr` must point to `data_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_extend_pcr(
+    index: u16,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let value = nsm::extend_pcr(index, bytes(data_ptr, data_len, "data")?)?;
+        Ok(Hex::encode(value))
+    })
+}
+
+/// Lock PCR `index` so it can no longer be extended; it is then included in attestations.
+/// Returns `0` on success or an error code.
+#[no_mangle]
+pub extern "C" fn nautilus_lock_pcr(index: u16) -> i32 {
+    ffi_status(|| nsm::lock_pcr(index))
+}
+
+/// Describe the NSM module.
+/// Returns JSON: `{ version: "<major>.<minor>.<patch>", module_id, max_pcrs, locked_pcrs: [..], digest }`.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (e.g. NSM unavailable), returns NULL.
+#[no_mangle]
+pub extern "C" fn nautilus_describe_nsm() -> *mut c_char {
+    ffi_cstr(|| to_json(&nsm::describe_nsm()?))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiagnosticsJson {
+    /// Version of this library.
+    pub library_version: String,
+    /// Whether NSM requests are served by the simulated module.
+    pub mock_nsm: bool,
+    /// Whether keypairs can be derived from caller-supplied seeds (test builds only).
+    pub seeded_keys: bool,
+    /// `DescribeNSM` output, or `null` if the NSM could not be reached.
+    pub nsm: Option<nsm::NsmDescription>,
+    /// Why `DescribeNSM` failed, if it did.
+    pub nsm_error: Option<String>,
+    /// Current RNG policy (see `nautilus_set_rng_policy`).
+    pub rng_policy: u8,
+    /// Entropy source of the last generated key (see `nautilus_last_entropy_source`).
+    pub last_entropy_source: u8,
+}
+
+/// Collect runtime diagnostics for health checks.
+/// Returns JSON: `{ library_version, mock_nsm, seeded_keys, nsm, nsm_error, rng_policy, last_entropy_source }`.
+/// An unreachable NSM is reported in `nsm_error` rather than failing the call.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+#[no_mangle]
+pub extern "C" fn nautilus_diagnostics() -> *mut c_char {
+    ffi_cstr(|| {
+        let (nsm, nsm_error) = match nsm::describe_nsm() {
+            Ok(description) => (Some(description), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        to_json(&DiagnosticsJson {
+            library_version: env!("CARGO_PKG_VERSION").to_string(),
+            mock_nsm: cfg!(feature = "mock-nsm"),
+            seeded_keys: cfg!(feature = "seeded-keys"),
+            nsm,
+            nsm_error,
+            rng_policy: rng::policy() as u8,
+            last_entropy_source: rng::last_entropy_source() as u8,
+        })
+    })
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum IntentScope {
     ProcessData = 0,
+    /// Key handover statements, only signed by `nautilus_rotate_keypair` (see [`rotation`]).
+    KeyRotation = 1,
+}
+
+impl TryFrom<u8> for IntentScope {
+    type Error = NautilusError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(IntentScope::ProcessData),
+            1 => Ok(IntentScope::KeyRotation),
+            other => Err(NautilusError::UnknownIntent(other)),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -123,59 +798,122 @@ pub struct ProcessedDataResponse<T> {
     pub signature: String,
 }
 
-fn to_signed_response<T: Serialize + Clone>(
-    kp: &Ed25519KeyPair,
+/// BCS-encode an intent message for signing.
+fn to_signing_payload<T: Serialize>(intent_msg: &IntentMessage<T>) -> NautilusResult<Vec<u8>> {
+    bcs::to_bytes(intent_msg).map_err(|e| NautilusError::Serialization(e.to_string()))
+}
+
+/// Serialize an FFI response object as JSON.
+fn to_json<T: Serialize>(value: &T) -> NautilusResult<String> {
+    serde_json::to_string(value).map_err(|e| NautilusError::Serialization(e.to_string()))
+}
+
+pub fn to_signed_response<T: Serialize + Clone>(
+    kp: &EnclaveKeyPair,
     payload: T,
     timestamp_ms: u64,
     intent: IntentScope,
-) -> ProcessedDataResponse<IntentMessage<T>> {
+) -> NautilusResult<ProcessedDataResponse<IntentMessage<T>>> {
     let intent_msg = IntentMessage {
         intent,
         timestamp_ms,
         data: payload.clone(),
     };
-    let signing_payload = bcs::to_bytes(&intent_msg).expect("bcs serialize");
+    let signing_payload = to_signing_payload(&intent_msg)?;
     let sig = kp.sign(&signing_payload);
-    ProcessedDataResponse {
+    Ok(ProcessedDataResponse {
         response: intent_msg,
         signature: Hex::encode(sig),
-    }
+    })
 }
 
 /// Sign an intent message whose data field is arbitrary bytes (passed by pointer/length).
 /// Returns JSON: `{ response: { intent, timestamp_ms, data: <base64> }, signature: <hex> }`.
 /// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (invalid handle or pointer, unknown intent), returns NULL.
 ///
 /// Parameters:
-/// - `ptr`: keypair pointer from `nautilus_generate_ed25519_keypair`.
+/// - `handle`: keypair handle from `nautilus_generate_keypair`.
 /// - `payload_ptr` / `payload_len`: raw bytes to include in the message.
 /// - `timestamp_ms`: UNIX epoch in milliseconds.
-/// - `intent`: intent scope as `u8` (current: `0` = ProcessData; future-safe).
+/// - `intent`: intent scope as `u8` (current: `0` = ProcessData; `1` is reserved for
+///   `nautilus_rotate_keypair`).
 ///
-/// Safety: `payload_ptr` must point to `payload_len` bytes; `ptr` must be non-NULL.
+/// Safety: `payload_ptr` must point to `payload_len` bytes.
 #[no_mangle]
 pub extern "C" fn nautilus_sign_intent_message_json(
-    ptr: *mut FfiKeyPair,
+    handle: KeyHandle,
     payload_ptr: *const u8,
     payload_len: usize,
     timestamp_ms: u64,
     intent: u8,
 ) -> *mut c_char {
-    let payload = unsafe { std::slice::from_raw_parts(payload_ptr, payload_len) }.to_vec();
-    let intent_scope = match intent { 0 => IntentScope::ProcessData, _ => IntentScope::ProcessData };
-    let intent_msg = IntentMessageBytes {
-        intent: intent_scope.clone(),
-        timestamp_ms,
-        data: serde_bytes::ByteBuf::from(payload.clone()),
-    };
-    let signing_payload = bcs::to_bytes(&IntentMessage { intent: intent_scope, timestamp_ms, data: payload })
-        .expect("bcs serialize");
-    let sig = unsafe { (&*ptr).inner.sign(&signing_payload) };
+    ffi_cstr(|| {
+        sign_json(
+            handle,
+            payload_ptr,
+            payload_len,
+            timestamp_ms,
+            intent,
+            default_sign,
+        )
+    })
+}
+
+fn sign_json(
+    handle: KeyHandle,
+    payload_ptr: *const u8,
+    payload_len: usize,
+    timestamp_ms: u64,
+    intent: u8,
+    sign: impl FnOnce(&EnclaveKeyPair, &[u8]) -> NautilusResult<Vec<u8>>,
+) -> NautilusResult<String> {
+    let signed = sign_intent(handle, payload_ptr, payload_len, timestamp_ms, intent, sign)?;
     let resp = ProcessedDataResponse {
-        response: intent_msg,
-        signature: Hex::encode(sig),
+        response: IntentMessageBytes {
+            intent: signed.message.intent,
+            timestamp_ms: signed.message.timestamp_ms,
+            data: serde_bytes::ByteBuf::from(signed.message.data),
+        },
+        signature: Hex::encode(signed.signature),
     };
-    to_cstr(serde_json::to_string(&resp).unwrap())
+    to_json(&resp)
+}
+
+/// Sign an intent message with an ECDSA key, choosing the message hash and whether
+/// the signature is recoverable. Returns the same JSON as `nautilus_sign_intent_message_json`;
+/// the signature is 64 bytes (`r || s`), or 65 bytes (`r || s || v`) if `recoverable`.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (invalid handle or pointer, unknown intent or hash, non-ECDSA key, recoverable
+/// Keccak-256 with a secp256r1 key), returns NULL.
+///
+/// Parameters are those of `nautilus_sign_intent_message_json`, plus:
+/// - `hash`: `0` = SHA-256 (Sui `ecdsa_k1`), `1` = Keccak-256 (EVM `ecrecover`).
+/// - `recoverable`: append the recovery id `v`: `0` / `1` with SHA-256, or `27` / `28`
+///   with Keccak-256, ready for EVM `ecrecover` (secp256k1 keys only).
+///
+/// Safety: `payload_ptr` must point to `payload_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_sign_intent_message_ecdsa(
+    handle: KeyHandle,
+    payload_ptr: *const u8,
+    payload_len: usize,
+    timestamp_ms: u64,
+    intent: u8,
+    hash: u8,
+    recoverable: bool,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let hash = keys::MessageHash::try_from(hash)?;
+        sign_json(
+            handle,
+            payload_ptr,
+            payload_len,
+            timestamp_ms,
+            intent,
+            |kp, msg| kp.sign_ecdsa(msg, hash, recoverable),
+        )
+    })
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -187,28 +925,492 @@ pub struct SignedBcsResponse {
 /// Sign an intent message and return the BCS-encoded message and signature as hex strings.
 /// Useful when the consumer needs raw BCS to submit on-chain or to other runtimes.
 /// Caller must free the returned C string via `nautilus_free_cstr`.
+/// On error (invalid handle or pointer, unknown intent), returns NULL.
 ///
 /// Parameters are identical to `nautilus_sign_intent_message_json`.
 ///
-/// Safety: `payload_ptr` must point to `payload_len` bytes; `ptr` must be non-NULL.
+/// Safety: `payload_ptr` must point to `payload_len` bytes.
 #[no_mangle]
 pub extern "C" fn nautilus_sign_intent_message_bcs(
-    ptr: *mut FfiKeyPair,
+    handle: KeyHandle,
     payload_ptr: *const u8,
     payload_len: usize,
     timestamp_ms: u64,
     intent: u8,
 ) -> *mut c_char {
-    let payload = unsafe { std::slice::from_raw_parts(payload_ptr, payload_len) }.to_vec();
-    let intent_scope = match intent { 0 => IntentScope::ProcessData, _ => IntentScope::ProcessData };
-    let intent_msg = IntentMessage { intent: intent_scope, timestamp_ms, data: payload };
-    let signing_payload = bcs::to_bytes(&intent_msg).expect("bcs serialize");
-    let sig = unsafe { (&*ptr).inner.sign(&signing_payload) };
-    let resp = SignedBcsResponse {
-        intent_message_bcs: Hex::encode(signing_payload),
-        signature: Hex::encode(sig),
+    ffi_cstr(|| sign_bcs(handle, payload_ptr, payload_len, timestamp_ms, intent))
+}
+
+fn sign_bcs(
+    handle: KeyHandle,
+    payload_ptr: *const u8,
+    payload_len: usize,
+    timestamp_ms: u64,
+    intent: u8,
+) -> NautilusResult<String> {
+    let signed = sign_intent(
+        handle,
+        payload_ptr,
+        payload_len,
+        timestamp_ms,
+        intent,
+        default_sign,
+    )?;
+    to_json(&SignedBcsResponse {
+        intent_message_bcs: Hex::encode(signed.bcs),
+        signature: Hex::encode(signed.signature),
+    })
+}
+
+/// Sign with the scheme's default algorithm (see [`EnclaveKeyPair::sign`]).
+fn default_sign(kp: &EnclaveKeyPair, msg: &[u8]) -> NautilusResult<Vec<u8>> {
+    Ok(kp.sign(msg))
+}
+
+/// An intent message over caller bytes, its BCS encoding and its signature.
+struct SignedIntent {
+    message: IntentMessage<Vec<u8>>,
+    bcs: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// Build an intent message over the caller payload, BCS-encode it and sign it with `sign`.
+fn sign_intent(
+    handle: KeyHandle,
+    payload_ptr: *const u8,
+    payload_len: usize,
+    timestamp_ms: u64,
+    intent: u8,
+    sign: impl FnOnce(&EnclaveKeyPair, &[u8]) -> NautilusResult<Vec<u8>>,
+) -> NautilusResult<SignedIntent> {
+    let kp = keypair(handle)?;
+    let payload = bytes(payload_ptr, payload_len, "payload")?.to_vec();
+    let intent_scope = IntentScope::try_from(intent)?;
+    if let IntentScope::KeyRotation = intent_scope {
+        // Otherwise the host could forge handovers to keys outside the enclave.
+        return Err(NautilusError::InvalidArgument(
+            "key rotation intents are only signed by nautilus_rotate_keypair".to_string(),
+        ));
+    }
+    let intent_msg = IntentMessage {
+        intent: intent_scope,
+        timestamp_ms,
+        data: payload,
     };
-    to_cstr(serde_json::to_string(&resp).unwrap())
+    let signing_payload = to_signing_payload(&intent_msg)?;
+    let signature = sign(&kp, &signing_payload)?;
+    Ok(SignedIntent {
+        message: intent_msg,
+        bcs: signing_payload,
+        signature,
+    })
+}
+
+/// Length of the signature at the start of `nautilus_sign_intent_message_bytes` output
+/// (Ed25519, or ECDSA `r || s`).
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Length of a BLS12-381 min-sig signature, which replaces [`SIGNATURE_LENGTH`] for BLS keys.
+pub const BLS_SIGNATURE_LENGTH: usize = 48;
+
+/// Length of a BLS12-381 min-sig public key.
+pub const BLS_PUBLIC_KEY_LENGTH: usize = 96;
+
+/// Sign an intent message and return the raw signature followed by the BCS-encoded message:
+/// `signature || intent_message_bcs`, with a 64-byte signature ([`SIGNATURE_LENGTH`]), or
+/// a 48-byte one for BLS12-381 keys ([`BLS_SIGNATURE_LENGTH`]). The signature length is
+/// also written to `signature_len_out` (if non-NULL), so callers need not know the scheme.
+/// Free the returned buffer via `nautilus_free_buffer`.
+/// On error (invalid handle or pointer, unknown intent), returns NULL and leaves
+/// `signature_len_out` untouched.
+///
+/// Other parameters are identical to `nautilus_sign_intent_message_json`.
+///
+/// Safety: `payload_ptr` must point to `payload_len` bytes; a non-NULL
+/// `signature_len_out` must be valid for writing one `usize`.
+#[no_mangle]
+pub extern "C" fn nautilus_sign_intent_message_bytes(
+    handle: KeyHandle,
+    payload_ptr: *const u8,
+    payload_len: usize,
+    timestamp_ms: u64,
+    intent: u8,
+    signature_len_out: *mut usize,
+) -> *mut FfiBuffer {
+    ffi_buffer(|| {
+        let mut signed = sign_intent(
+            handle,
+            payload_ptr,
+            payload_len,
+            timestamp_ms,
+            intent,
+            default_sign,
+        )?;
+        if !signature_len_out.is_null() {
+            unsafe { *signature_len_out = signed.signature.len() };
+        }
+        signed.signature.extend_from_slice(&signed.bcs);
+        Ok(signed.signature)
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ObjectRefJson {
+    pub object_id: String,
+    pub version: u64,
+    /// Base58 object digest, as printed by the Sui CLI.
+    pub digest: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SharedObjectRefJson {
+    pub object_id: String,
+    pub initial_shared_version: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RegisterEnclaveTxJson {
+    /// Hex attestation document, as returned by `nautilus_get_attestation`.
+    pub attestation: String,
+    pub enclave_package_id: String,
+    pub enclave_config: SharedObjectRefJson,
+    pub app_package_id: String,
+    pub module_name: String,
+    pub otw_name: String,
+    pub sender: String,
+    pub gas_payment: Vec<ObjectRefJson>,
+    pub gas_price: u64,
+    pub gas_budget: u64,
 }
 
-// FFI-only: no error type exported.
+impl TryFrom<RegisterEnclaveTxJson> for sui_tx::RegisterEnclaveTx {
+    type Error = NautilusError;
+
+    fn try_from(json: RegisterEnclaveTxJson) -> Result<Self, Self::Error> {
+        Ok(sui_tx::RegisterEnclaveTx {
+            attestation: Hex::decode(json.attestation.trim_start_matches("0x")).map_err(|e| {
+                NautilusError::InvalidArgument(format!("invalid attestation hex: {e}"))
+            })?,
+            enclave_package_id: sui_tx::parse_address(&json.enclave_package_id)?,
+            enclave_config: sui_tx::SharedObjectRef {
+                object_id: sui_tx::parse_address(&json.enclave_config.object_id)?,
+                initial_shared_version: json.enclave_config.initial_shared_version,
+            },
+            app_package_id: sui_tx::parse_address(&json.app_package_id)?,
+            module_name: json.module_name,
+            otw_name: json.otw_name,
+            sender: sui_tx::parse_address(&json.sender)?,
+            gas_payment: json
+                .gas_payment
+                .iter()
+                .map(|gas| {
+                    Ok(sui_tx::ObjectRef {
+                        object_id: sui_tx::parse_address(&gas.object_id)?,
+                        version: gas.version,
+                        digest: sui_tx::parse_digest(&gas.digest)?,
+                    })
+                })
+                .collect::<NautilusResult<_>>()?,
+            gas_price: json.gas_price,
+            gas_budget: json.gas_budget,
+        })
+    }
+}
+
+/// Build the BCS `TransactionData` registering an enclave on Sui
+/// (`load_nitro_attestation` then `enclave::register_enclave<T>`), from JSON:
+/// `{ attestation: <hex>, enclave_package_id, enclave_config: { object_id, initial_shared_version },
+///    app_package_id, module_name, otw_name, sender,
+///    gas_payment: [{ object_id, version, digest: <base58> }], gas_price, gas_budget }`.
+/// The transaction is not signed. Free the returned buffer via `nautilus_free_buffer`.
+/// On error (malformed JSON, address or digest), returns NULL.
+///
+/// Safety: `json_ptr` must point to `json_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_build_register_enclave_tx(
+    json_ptr: *const u8,
+    json_len: usize,
+) -> *mut FfiBuffer {
+    ffi_buffer(|| {
+        let json: RegisterEnclaveTxJson =
+            serde_json::from_slice(bytes(json_ptr, json_len, "json")?).map_err(|e| {
+                NautilusError::InvalidArgument(format!("invalid transaction JSON: {e}"))
+            })?;
+        sui_tx::RegisterEnclaveTx::try_from(json)?.to_bytes()
+    })
+}
+
+/// Verify `signature` over `msg` (e.g. the BCS intent message returned by
+/// `nautilus_sign_intent_message_bcs`) for a `scheme` public key, as produced by the
+/// default signing functions (Ed25519, ECDSA over SHA-256 non-recoverable, or
+/// BLS12-381 min-sig with a 96-byte G2 public key and a 48-byte G1 signature).
+/// Returns `0` if the signature is valid, or an error code (`InvalidSignature` if it
+/// does not verify or the key or signature is malformed).
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_verify_signature(
+    scheme: u8,
+    public_key_ptr: *const u8,
+    public_key_len: usize,
+    msg_ptr: *const u8,
+    msg_len: usize,
+    signature_ptr: *const u8,
+    signature_len: usize,
+) -> i32 {
+    ffi_status(|| {
+        keys::verify(
+            keys::KeyScheme::try_from(scheme)?,
+            bytes(public_key_ptr, public_key_len, "public_key")?,
+            bytes(msg_ptr, msg_len, "msg")?,
+            bytes(signature_ptr, signature_len, "signature")?,
+        )
+    })
+}
+
+/// Return the PEM-encoded root certificate of the simulated NSM (`mock-nsm` feature only),
+/// to be trusted when verifying mock attestation documents.
+/// Caller must free the returned C string via `nautilus_free_cstr`.
+#[cfg(feature = "mock-nsm")]
+#[no_mangle]
+pub extern "C" fn nautilus_mock_nsm_root_certificate_pem() -> *mut c_char {
+    ffi_cstr(mock_nsm::root_certificate_pem)
+}
+
+/// Set PCR `index` of the simulated NSM to the 48 bytes at `value_ptr` (`mock-nsm` feature only).
+/// Returns `0` on success or an error code.
+///
+/// Safety: `value_ptr` must point to `value_len` bytes.
+#[cfg(feature = "mock-nsm")]
+#[no_mangle]
+pub extern "C" fn nautilus_mock_nsm_set_pcr(
+    index: u16,
+    value_ptr: *const u8,
+    value_len: usize,
+) -> i32 {
+    ffi_status(|| mock_nsm::set_pcr(index as usize, bytes(value_ptr, value_len, "value")?))
+}
+
+/// Aggregate BLS12-381 signatures over the same message (e.g. one intent message signed
+/// by several enclave replicas with the same payload and timestamp) into one signature.
+/// `signatures_ptr` holds `signatures_len / 48` concatenated 48-byte signatures.
+/// Returns the 48-byte aggregate; free the buffer via `nautilus_free_buffer`.
+/// On error (no or malformed signatures), returns NULL.
+///
+/// Safety: `signatures_ptr` must point to `signatures_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_aggregate_bls_signatures(
+    signatures_ptr: *const u8,
+    signatures_len: usize,
+) -> *mut FfiBuffer {
+    ffi_buffer(|| {
+        let signatures = chunks(
+            bytes(signatures_ptr, signatures_len, "signatures")?,
+            BLS_SIGNATURE_LENGTH,
+            "signatures",
+        )?;
+        keys::aggregate_bls_signatures(&signatures)
+    })
+}
+
+/// Verify an aggregate BLS12-381 signature over `msg` by every public key in
+/// `public_keys_ptr` (`public_keys_len / 96` concatenated 96-byte keys). Only pass
+/// keys taken from attestation documents: BLS aggregation assumes honest keys.
+/// Returns `0` if the signature is valid, or an error code (`InvalidSignature` if it
+/// does not verify or a key or the signature is malformed, `InvalidArgument` if no
+/// keys are given).
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_verify_bls_aggregate(
+    public_keys_ptr: *const u8,
+    public_keys_len: usize,
+    msg_ptr: *const u8,
+    msg_len: usize,
+    signature_ptr: *const u8,
+    signature_len: usize,
+) -> i32 {
+    ffi_status(|| {
+        let public_keys = chunks(
+            bytes(public_keys_ptr, public_keys_len, "public_keys")?,
+            BLS_PUBLIC_KEY_LENGTH,
+            "public_keys",
+        )?;
+        keys::verify_bls_aggregate(
+            &public_keys,
+            bytes(msg_ptr, msg_len, "msg")?,
+            bytes(signature_ptr, signature_len, "signature")?,
+        )
+    })
+}
+
+/// Split concatenated fixed-size items, rejecting a trailing partial item.
+fn chunks<'a>(bytes: &'a [u8], size: usize, name: &str) -> NautilusResult<Vec<&'a [u8]>> {
+    if !bytes.len().is_multiple_of(size) {
+        return Err(NautilusError::InvalidArgument(format!(
+            "{name} length is not a multiple of {size}"
+        )));
+    }
+    Ok(bytes.chunks(size).collect())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiSigMemberJson {
+    /// Scheme flag byte, as in `nautilus_generate_keypair`.
+    pub scheme: u8,
+    /// Hex public key, as returned by `nautilus_get_public_key_hex`.
+    pub public_key: String,
+    pub weight: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiSigPublicKeyJson {
+    pub members: Vec<MultiSigMemberJson>,
+    pub threshold: u16,
+}
+
+impl TryFrom<MultiSigPublicKeyJson> for multisig::MultiSigPublicKey {
+    type Error = NautilusError;
+
+    fn try_from(json: MultiSigPublicKeyJson) -> Result<Self, Self::Error> {
+        multisig::MultiSigPublicKey::new(
+            json.members
+                .into_iter()
+                .map(|member| {
+                    Ok(multisig::MultiSigMember {
+                        scheme: keys::KeyScheme::try_from(member.scheme)?,
+                        public_key: decode_hex(&member.public_key, "public key")?,
+                        weight: member.weight,
+                    })
+                })
+                .collect::<NautilusResult<_>>()?,
+            json.threshold,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiSigAddressJson {
+    /// `0x`-prefixed hex Sui address of the multisig.
+    pub address: String,
+    /// Base64 flagged multisig public key (`0x03 || BCS(MultiSigPublicKey)`).
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PartialSignatureJson {
+    /// Hex public key of the signing member.
+    pub public_key: String,
+    /// Hex signature over the message, as returned by `nautilus_sign_intent_message_bcs`.
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CombineMultiSigJson {
+    pub multisig: MultiSigPublicKeyJson,
+    pub signatures: Vec<PartialSignatureJson>,
+}
+
+fn decode_hex(s: &str, name: &str) -> NautilusResult<Vec<u8>> {
+    Hex::decode(s.trim_start_matches("0x"))
+        .map_err(|e| NautilusError::InvalidArgument(format!("invalid {name} hex: {e}")))
+}
+
+fn parse_multisig_json<T: serde::de::DeserializeOwned>(
+    json_ptr: *const u8,
+    json_len: usize,
+) -> NautilusResult<T> {
+    serde_json::from_slice(bytes(json_ptr, json_len, "json")?)
+        .map_err(|e| NautilusError::InvalidArgument(format!("invalid multisig JSON: {e}")))
+}
+
+/// Build the Sui multisig public key of several enclave replicas, from JSON:
+/// `{ members: [{ scheme, public_key: <hex>, weight }], threshold }`.
+/// Members sign in any combination whose weights add up to `threshold`; up to 10
+/// Ed25519, secp256k1 or secp256r1 keys (not BLS12-381), in an order that is part
+/// of the address. Returns JSON `{ address: <0x hex>, public_key: <base64> }`
+/// (newly allocated C string; free with `nautilus_free_cstr`).
+/// On error (malformed JSON or key, zero weight, duplicate key, unreachable threshold), returns NULL.
+///
+/// Safety: `json_ptr` must point to `json_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_build_multisig_public_key(
+    json_ptr: *const u8,
+    json_len: usize,
+) -> *mut c_char {
+    ffi_cstr(|| {
+        let json: MultiSigPublicKeyJson = parse_multisig_json(json_ptr, json_len)?;
+        let multisig = multisig::MultiSigPublicKey::try_from(json)?;
+        to_json(&MultiSigAddressJson {
+            address: format!("0x{}", Hex::encode(multisig.address())),
+            public_key: Base64::encode(multisig.to_bytes()?),
+        })
+    })
+}
+
+/// Combine replica signatures over the same `msg` (e.g. one BCS intent message
+/// signed by every replica with the same payload and timestamp) into a Sui multisig
+/// signature, `0x03 || BCS(MultiSig)`, from JSON:
+/// `{ multisig: { members, threshold }, signatures: [{ public_key: <hex>, signature: <hex> }] }`.
+/// Signatures cover the raw `msg`, not the `Blake2b-256(intent || msg)` digest Sui
+/// signs for transactions and personal messages, so Sui nodes and tooling reject the
+/// result; check it with `nautilus_verify_multisig`.
+/// Free the returned buffer via `nautilus_free_buffer`.
+/// On error, returns NULL: `InvalidSignature` if a signature does not verify over
+/// `msg` or the signers' weights are below the threshold, `InvalidArgument` for a
+/// malformed committee or a signer that is not a member.
+///
+/// Safety: `json_ptr` must point to `json_len` bytes, `msg_ptr` to `msg_len` bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_combine_multisig_signatures(
+    json_ptr: *const u8,
+    json_len: usize,
+    msg_ptr: *const u8,
+    msg_len: usize,
+) -> *mut FfiBuffer {
+    ffi_buffer(|| {
+        let json: CombineMultiSigJson = parse_multisig_json(json_ptr, json_len)?;
+        let multisig = multisig::MultiSigPublicKey::try_from(json.multisig)?;
+        let signatures = json
+            .signatures
+            .iter()
+            .map(|partial| {
+                Ok((
+                    decode_hex(&partial.public_key, "public key")?,
+                    decode_hex(&partial.signature, "signature")?,
+                ))
+            })
+            .collect::<NautilusResult<Vec<_>>>()?;
+        let pairs: Vec<(&[u8], &[u8])> = signatures
+            .iter()
+            .map(|(public_key, signature)| (public_key.as_slice(), signature.as_slice()))
+            .collect();
+        multisig.combine(bytes(msg_ptr, msg_len, "msg")?, &pairs)
+    })
+}
+
+/// Verify a Sui multisig signature (as returned by `nautilus_combine_multisig_signatures`)
+/// over `msg` by the multisig described by JSON `{ members, threshold }`, as passed to
+/// `nautilus_build_multisig_public_key`.
+/// Returns `0` if the signature is by that multisig, every included signature
+/// verifies and their weights reach the threshold, or an error code
+/// (`InvalidSignature` otherwise, `InvalidArgument` for a malformed committee).
+///
+/// Safety: non-NULL buffer pointers must point to the given number of bytes.
+#[no_mangle]
+pub extern "C" fn nautilus_verify_multisig(
+    json_ptr: *const u8,
+    json_len: usize,
+    msg_ptr: *const u8,
+    msg_len: usize,
+    signature_ptr: *const u8,
+    signature_len: usize,
+) -> i32 {
+    ffi_status(|| {
+        let json: MultiSigPublicKeyJson = parse_multisig_json(json_ptr, json_len)?;
+        multisig::MultiSigPublicKey::try_from(json)?.verify(
+            bytes(msg_ptr, msg_len, "msg")?,
+            bytes(signature_ptr, signature_len, "signature")?,
+        )
+    })
+}
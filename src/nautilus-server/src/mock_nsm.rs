@@ -0,0 +1,297 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Simulated NSM for development and CI outside Nitro Enclaves (`mock-nsm` feature).
+//!
+//! Attestation documents are structurally identical to real ones: a COSE_Sign1
+//! (ES384) over a CBOR document whose certificate chains to a test root CA
+//! generated on first use. Verify them with [`crate::verify`], trusting
+//! [`root_certificate_der`] instead of the AWS root.
+//!
+//! PCRs start at zero, with PCR0-15 locked as after a Nitro boot; documents
+//! include every locked PCR. Set values with [`set_pcr`], or at startup with the
+//! `NAUTILUS_MOCK_PCRS` environment variable: comma-separated `<index>=<hex>`
+//! pairs, e.g. `NAUTILUS_MOCK_PCRS=0=ab..,1=cd..,2=ef..`. `DescribePCR`,
+//! `ExtendPCR`, `LockPCR` and `DescribeNSM` behave as on the NSM; `GetRandom`
+//! returns OS randomness.
+//!
+//! Never enable this feature in enclave builds: anyone can mint these documents.
+use crate::error::{NautilusError, NautilusResult};
+use fastcrypto::encoding::{Encoding, Hex};
+use nsm_api::api::{AttestationDoc, Digest, ErrorCode, Request, Response};
+use p384::ecdsa::signature::Signer;
+use p384::ecdsa::{Signature, SigningKey};
+use p384::pkcs8::EncodePrivateKey;
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair, KeyUsagePurpose};
+use serde_bytes::ByteBuf;
+use sha2::{Digest as _, Sha384};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Module ID reported in mock documents.
+pub const MOCK_MODULE_ID: &str = "i-00000000000000000-enc0000000000000000";
+
+/// Environment variable holding initial PCR values.
+pub const MOCK_PCRS_ENV: &str = "NAUTILUS_MOCK_PCRS";
+
+/// Number of PCRs exposed by the mock, matching the Nitro NSM.
+const MAX_PCRS: usize = 32;
+
+/// PCRs locked at boot (reserved for the Nitro hypervisor and enclave image).
+const BOOT_LOCKED_PCRS: u16 = 16;
+
+/// SHA-384 PCR length.
+const PCR_LEN: usize = 48;
+
+struct MockNsm {
+    root_der: Vec<u8>,
+    root_pem: String,
+    leaf_der: Vec<u8>,
+    leaf_key: SigningKey,
+    pcrs: Vec<Vec<u8>>,
+    locked: BTreeSet<u16>,
+}
+
+static MOCK: Mutex<Option<MockNsm>> = Mutex::new(None);
+
+/// Run `f` on the mock state, creating the test CA and applying `NAUTILUS_MOCK_PCRS` on first use.
+fn with_mock<T>(f: impl FnOnce(&mut MockNsm) -> NautilusResult<T>) -> NautilusResult<T> {
+    let mut guard = MOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    if guard.is_none() {
+        *guard = Some(MockNsm::new()?);
+    }
+    f(guard.as_mut().expect("initialized above"))
+}
+
+fn setup_error(e: impl std::fmt::Display) -> NautilusError {
+    NautilusError::NsmRequestFailed(format!("mock NSM setup failed: {e}"))
+}
+
+impl MockNsm {
+    fn new() -> NautilusResult<Self> {
+        let root_key = SigningKey::random(&mut rand::thread_rng());
+        let root_key_pair = rcgen_key_pair(&root_key)?;
+        let mut root_params = CertificateParams::default();
+        root_params
+            .distinguished_name
+            .push(DnType::CommonName, "nautilus-mock-nsm-root");
+        root_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        root_params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        let root = root_params
+            .self_signed(&root_key_pair)
+            .map_err(setup_error)?;
+
+        let leaf_key = SigningKey::random(&mut rand::thread_rng());
+        let leaf_key_pair = rcgen_key_pair(&leaf_key)?;
+        let mut leaf_params = CertificateParams::default();
+        leaf_params
+            .distinguished_name
+            .push(DnType::CommonName, MOCK_MODULE_ID);
+        leaf_params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        let leaf = leaf_params
+            .signed_by(&leaf_key_pair, &root, &root_key_pair)
+            .map_err(setup_error)?;
+
+        let mut nsm = MockNsm {
+            root_der: root.der().to_vec(),
+            root_pem: root.pem(),
+            leaf_der: leaf.der().to_vec(),
+            leaf_key,
+            pcrs: vec![vec![0; PCR_LEN]; MAX_PCRS],
+            locked: (0..BOOT_LOCKED_PCRS).collect(),
+        };
+        if let Ok(spec) = std::env::var(MOCK_PCRS_ENV) {
+            for (index, value) in parse_pcrs(&spec)? {
+                nsm.set_pcr(index, value)?;
+            }
+        }
+        Ok(nsm)
+    }
+
+    fn set_pcr(&mut self, index: usize, value: Vec<u8>) -> NautilusResult<()> {
+        if value.len() != PCR_LEN {
+            return Err(NautilusError::InvalidArgument(format!(
+                "PCR value must be {PCR_LEN} bytes, got {}",
+                value.len()
+            )));
+        }
+        let slot = self.pcrs.get_mut(index).ok_or_else(|| {
+            NautilusError::InvalidArgument(format!("PCR index {index} out of range"))
+        })?;
+        *slot = value;
+        Ok(())
+    }
+
+    /// Build and sign a COSE_Sign1 attestation document.
+    fn attest(
+        &self,
+        user_data: Option<ByteBuf>,
+        nonce: Option<ByteBuf>,
+        public_key: Option<ByteBuf>,
+    ) -> NautilusResult<Vec<u8>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let doc = AttestationDoc {
+            module_id: MOCK_MODULE_ID.to_string(),
+            digest: Digest::SHA384,
+            timestamp,
+            pcrs: self
+                .locked
+                .iter()
+                .map(|&index| {
+                    let index = index as usize;
+                    (index, ByteBuf::from(self.pcrs[index].clone()))
+                })
+                .collect(),
+            certificate: ByteBuf::from(self.leaf_der.clone()),
+            cabundle: vec![ByteBuf::from(self.root_der.clone())],
+            public_key,
+            user_data,
+            nonce,
+        };
+        let payload = doc.to_binary();
+
+        // Protected header {1 (alg): -35 (ES384)}, as emitted by the Nitro NSM.
+        let protected = serde_cbor::to_vec(&BTreeMap::from([(1, -35)]))
+            .map_err(|e| NautilusError::Serialization(e.to_string()))?;
+        let sig_structure = serde_cbor::to_vec(&(
+            "Signature1",
+            serde_bytes::Bytes::new(&protected),
+            serde_bytes::Bytes::new(&[]),
+            serde_bytes::Bytes::new(&payload),
+        ))
+        .map_err(|e| NautilusError::Serialization(e.to_string()))?;
+        let signature: Signature = self.leaf_key.sign(&sig_structure);
+
+        serde_cbor::to_vec(&(
+            serde_bytes::Bytes::new(&protected),
+            BTreeMap::<i64, i64>::new(),
+            serde_bytes::Bytes::new(&payload),
+            serde_bytes::Bytes::new(&signature.to_bytes()),
+        ))
+        .map_err(|e| NautilusError::Serialization(e.to_string()))
+    }
+}
+
+fn rcgen_key_pair(key: &SigningKey) -> NautilusResult<KeyPair> {
+    let der = key.to_pkcs8_der().map_err(setup_error)?;
+    KeyPair::try_from(der.as_bytes()).map_err(setup_error)
+}
+
+/// Parse `<index>=<hex>` pairs separated by commas.
+fn parse_pcrs(spec: &str) -> NautilusResult<Vec<(usize, Vec<u8>)>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || {
+                NautilusError::InvalidArgument(format!("invalid {MOCK_PCRS_ENV} entry: {entry}"))
+            };
+            let (index, value) = entry.split_once('=').ok_or_else(invalid)?;
+            let index = index.trim().parse().map_err(|_| invalid())?;
+            let value =
+                Hex::decode(value.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+            Ok((index, value))
+        })
+        .collect()
+}
+
+/// Serve an NSM request from the simulated module.
+pub(crate) fn process_request(request: Request) -> NautilusResult<Response> {
+    with_mock(|nsm| match request {
+        Request::Attestation {
+            user_data,
+            nonce,
+            public_key,
+        } => Ok(Response::Attestation {
+            document: nsm.attest(user_data, nonce, public_key)?,
+        }),
+        Request::DescribePCR { index } => Ok(match nsm.pcrs.get(index as usize) {
+            Some(value) => Response::DescribePCR {
+                lock: nsm.locked.contains(&index),
+                data: value.clone(),
+            },
+            None => Response::Error(ErrorCode::InvalidIndex),
+        }),
+        Request::ExtendPCR { index, data } => Ok(match nsm.pcrs.get_mut(index as usize) {
+            None => Response::Error(ErrorCode::InvalidIndex),
+            Some(_) if nsm.locked.contains(&index) => Response::Error(ErrorCode::ReadOnlyIndex),
+            Some(value) => {
+                // PCR_new = SHA384(PCR_old || data)
+                *value = Sha384::new()
+                    .chain_update(&*value)
+                    .chain_update(&data)
+                    .finalize()
+                    .to_vec();
+                Response::ExtendPCR {
+                    data: value.clone(),
+                }
+            }
+        }),
+        Request::LockPCR { index } => Ok(if (index as usize) < MAX_PCRS {
+            nsm.locked.insert(index);
+            Response::LockPCR
+        } else {
+            Response::Error(ErrorCode::InvalidIndex)
+        }),
+        Request::DescribeNSM => Ok(Response::DescribeNSM {
+            version_major: 1,
+            version_minor: 0,
+            version_patch: 0,
+            module_id: MOCK_MODULE_ID.to_string(),
+            max_pcrs: MAX_PCRS as u16,
+            locked_pcrs: nsm.locked.clone(),
+            digest: Digest::SHA384,
+        }),
+        Request::GetRandom => {
+            let mut random = vec![0; 256];
+            rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut random);
+            Ok(Response::GetRandom { random })
+        }
+        _ => Ok(Response::Error(ErrorCode::InvalidOperation)),
+    })
+}
+
+/// DER-encoded test root certificate that mock documents chain to.
+pub fn root_certificate_der() -> NautilusResult<Vec<u8>> {
+    with_mock(|nsm| Ok(nsm.root_der.clone()))
+}
+
+/// PEM-encoded test root certificate that mock documents chain to.
+pub fn root_certificate_pem() -> NautilusResult<String> {
+    with_mock(|nsm| Ok(nsm.root_pem.clone()))
+}
+
+/// Set the value of PCR `index` (48 bytes, SHA-384), regardless of its lock state.
+pub fn set_pcr(index: usize, value: &[u8]) -> NautilusResult<()> {
+    with_mock(|nsm| nsm.set_pcr(index, value.to_vec()))
+}
+
+#[cfg(all(test, feature = "mock-nsm"))]
+mod tests {
+    use super::*;
+    use crate::attestation::parse_attestation;
+    use crate::nsm::request_attestation;
+
+    #[test]
+    fn attestation_round_trip() {
+        let document = request_attestation(
+            b"public key",
+            Some(ByteBuf::from(b"user data".to_vec())),
+            Some(ByteBuf::from(b"nonce".to_vec())),
+        )
+        .unwrap();
+        let doc = parse_attestation(&document).unwrap();
+
+        assert_eq!(doc.module_id, MOCK_MODULE_ID);
+        assert_eq!(doc.digest, "SHA384");
+        assert_eq!(doc.public_key.as_deref(), Some(&b"public key"[..]));
+        assert_eq!(doc.user_data.as_deref(), Some(&b"user data"[..]));
+        assert_eq!(doc.nonce.as_deref(), Some(&b"nonce"[..]));
+        assert_eq!(doc.cabundle, vec![root_certificate_der().unwrap()]);
+        assert!((0..BOOT_LOCKED_PCRS as usize).all(|index| doc.pcrs.contains_key(&index)));
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sui multisig over enclave replica keys.
+//!
+//! A [`MultiSigPublicKey`] lists replica public keys with weights and a threshold;
+//! its Sui address is the Blake2b-256 of `0x03 || threshold || (flag || pk || weight)*`.
+//! Signatures from several replicas over the same message (e.g. one BCS
+//! `IntentMessage` with the same payload and timestamp) are combined into a Sui
+//! multisig signature, `0x03 || BCS(MultiSig)`, valid once the signers' weights
+//! reach the threshold. Only Ed25519, secp256k1 and secp256r1 keys can take part.
+//!
+//! Member signatures cover `msg` itself, as produced by [`keys::EnclaveKeyPair::sign`]
+//! (ECDSA hashes it with SHA-256), not the `Blake2b-256(intent || msg)` digest that
+//! Sui keys sign for transactions and personal messages. The encoding is Sui's, but
+//! Sui nodes and tooling will not accept these signatures; check them with
+//! [`MultiSigPublicKey::verify`] (or in Move, member by member, with
+//! `enclave::verify_signature`).
+//!
+//! The wire types mirror `sui-types`, where keys and signatures are fixed-size
+//! byte arrays (no length prefix) inside enums indexed by scheme.
+use crate::error::{NautilusError, NautilusResult};
+use crate::keys::{self, KeyScheme};
+use crate::sui_tx::Address;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Sui signature flag of multisig public keys, addresses and signatures.
+pub const MULTISIG_FLAG: u8 = 0x03;
+
+/// Maximum number of keys in a Sui multisig.
+pub const MAX_SIGNERS: usize = 10;
+
+/// A weighted member of a multisig.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiSigMember {
+    pub scheme: KeyScheme,
+    pub public_key: Vec<u8>,
+    pub weight: u8,
+}
+
+/// A Sui multisig public key: weighted members and the weight needed to sign.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiSigPublicKey {
+    members: Vec<MultiSigMember>,
+    threshold: u16,
+}
+
+fn invalid(reason: impl std::fmt::Display) -> NautilusError {
+    NautilusError::InvalidArgument(format!("invalid multisig: {reason}"))
+}
+
+impl MultiSigPublicKey {
+    /// Check the rules Sui enforces: 1 to [`MAX_SIGNERS`] distinct supported keys with
+    /// non-zero weights, and a non-zero threshold reachable by all members together.
+    pub fn new(members: Vec<MultiSigMember>, threshold: u16) -> NautilusResult<Self> {
+        if members.is_empty() || members.len() > MAX_SIGNERS {
+            return Err(invalid(format!("{} members", members.len())));
+        }
+        if threshold == 0 {
+            return Err(invalid("zero threshold"));
+        }
+        for (i, member) in members.iter().enumerate() {
+            WirePublicKey::new(member.scheme, &member.public_key)?;
+            if member.weight == 0 {
+                return Err(invalid("zero weight"));
+            }
+            if members[i + 1..]
+                .iter()
+                .any(|other| other.public_key == member.public_key)
+            {
+                return Err(invalid("duplicate public key"));
+            }
+        }
+        let total: u16 = members.iter().map(|member| member.weight as u16).sum();
+        if total < threshold {
+            return Err(invalid(format!(
+                "threshold {threshold} above total weight {total}"
+            )));
+        }
+        Ok(MultiSigPublicKey { members, threshold })
+    }
+
+    pub fn members(&self) -> &[MultiSigMember] {
+        &self.members
+    }
+
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Sui address of the multisig.
+    pub fn address(&self) -> Address {
+        let mut hasher = Blake2b256::default();
+        hasher.update([MULTISIG_FLAG]);
+        hasher.update(self.threshold.to_le_bytes());
+        for member in &self.members {
+            hasher.update([member.scheme as u8]);
+            hasher.update(&member.public_key);
+            hasher.update([member.weight]);
+        }
+        hasher.finalize().digest
+    }
+
+    /// Flagged public key, `0x03 || BCS(MultiSigPublicKey)`, as Sui prints it in base64.
+    pub fn to_bytes(&self) -> NautilusResult<Vec<u8>> {
+        flagged(&self.to_wire()?)
+    }
+
+    /// Combine `(public key, signature)` pairs from members into a Sui multisig
+    /// signature, `0x03 || BCS(MultiSig)`. Each signature must verify over the raw
+    /// `msg` (not a Sui intent digest, see the module docs) and the signers' weights
+    /// must reach the threshold.
+    pub fn combine(&self, msg: &[u8], signatures: &[(&[u8], &[u8])]) -> NautilusResult<Vec<u8>> {
+        let mut signed: Vec<(usize, WireSignature)> = Vec::with_capacity(signatures.len());
+        for (public_key, signature) in signatures {
+            let index = self
+                .members
+                .iter()
+                .position(|member| member.public_key == *public_key)
+                .ok_or_else(|| invalid("signature from a non-member key"))?;
+            if signed.iter().any(|(other, _)| *other == index) {
+                return Err(invalid("duplicate signer"));
+            }
+            let scheme = self.members[index].scheme;
+            keys::verify(scheme, public_key, msg, signature)?;
+            signed.push((index, WireSignature::new(scheme, signature)?));
+        }
+        self.check_weight(signed.iter().map(|(index, _)| *index))?;
+        // Sui matches signatures to the bitmap's set bits in ascending order.
+        signed.sort_by_key(|(index, _)| *index);
+        flagged(&WireMultiSig {
+            bitmap: signed
+                .iter()
+                .fold(0, |bitmap, (index, _)| bitmap | 1 << index),
+            sigs: signed.into_iter().map(|(_, sig)| sig).collect(),
+            multisig_pk: self.to_wire()?,
+        })
+    }
+
+    /// Verify a Sui multisig signature over `msg` by this multisig.
+    pub fn verify(&self, msg: &[u8], multisig: &[u8]) -> NautilusResult<()> {
+        if verify_any(msg, multisig)? != *self {
+            return Err(NautilusError::InvalidSignature(
+                "signed by a different multisig".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_weight(&self, signers: impl Iterator<Item = usize>) -> NautilusResult<()> {
+        let weight: u16 = signers.map(|index| self.members[index].weight as u16).sum();
+        if weight < self.threshold {
+            return Err(NautilusError::InvalidSignature(format!(
+                "signers' weight {weight} is below the threshold {}",
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+
+    fn to_wire(&self) -> NautilusResult<WireMultiSigPublicKey> {
+        Ok(WireMultiSigPublicKey {
+            pk_map: self
+                .members
+                .iter()
+                .map(|member| {
+                    Ok((
+                        WirePublicKey::new(member.scheme, &member.public_key)?,
+                        member.weight,
+                    ))
+                })
+                .collect::<NautilusResult<_>>()?,
+            threshold: self.threshold,
+        })
+    }
+}
+
+/// Verify a Sui multisig signature over `msg`: every included signature must verify
+/// for its member key and their weights must reach the threshold.
+/// Returns the multisig public key the signature carries, which callers must check
+/// against the expected one (see [`MultiSigPublicKey::verify`]).
+pub fn verify_any(msg: &[u8], multisig: &[u8]) -> NautilusResult<MultiSigPublicKey> {
+    let malformed =
+        |reason: &str| NautilusError::InvalidSignature(format!("malformed multisig: {reason}"));
+    let bcs_bytes = match multisig.split_first() {
+        Some((&MULTISIG_FLAG, rest)) => rest,
+        _ => return Err(malformed("missing multisig flag")),
+    };
+    let wire: WireMultiSig = bcs::from_bytes(bcs_bytes).map_err(|e| malformed(&e.to_string()))?;
+    let multisig_pk = MultiSigPublicKey::new(
+        wire.multisig_pk
+            .pk_map
+            .into_iter()
+            .map(|(pk, weight)| {
+                let (scheme, public_key) = pk.into_parts();
+                MultiSigMember {
+                    scheme,
+                    public_key,
+                    weight,
+                }
+            })
+            .collect(),
+        wire.multisig_pk.threshold,
+    )
+    .map_err(|e| malformed(&e.to_string()))?;
+    let signers: Vec<usize> = (0..16)
+        .filter(|bit| wire.bitmap & (1 << bit) != 0)
+        .collect();
+    if signers.len() != wire.sigs.len()
+        || signers
+            .iter()
+            .any(|&index| index >= multisig_pk.members.len())
+    {
+        return Err(malformed("bitmap does not match signatures"));
+    }
+    for (&index, sig) in signers.iter().zip(&wire.sigs) {
+        let member = &multisig_pk.members[index];
+        let (scheme, signature) = sig.parts();
+        if scheme != member.scheme {
+            return Err(malformed("signature scheme does not match its key"));
+        }
+        keys::verify(scheme, &member.public_key, msg, signature)?;
+    }
+    multisig_pk.check_weight(signers.into_iter())?;
+    Ok(multisig_pk)
+}
+
+fn flagged<T: Serialize>(value: &T) -> NautilusResult<Vec<u8>> {
+    let mut bytes = vec![MULTISIG_FLAG];
+    bytes.extend(bcs::to_bytes(value).map_err(|e| NautilusError::Serialization(e.to_string()))?);
+    Ok(bytes)
+}
+
+// BCS wire types, declared in the same order (and so with the same variant
+// indices) as their `sui-types` counterparts.
+
+#[derive(Serialize, Deserialize)]
+struct WireMultiSig {
+    sigs: Vec<WireSignature>,
+    bitmap: u16,
+    multisig_pk: WireMultiSigPublicKey,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireMultiSigPublicKey {
+    pk_map: Vec<(WirePublicKey, u8)>,
+    threshold: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+enum WirePublicKey {
+    Ed25519(FixedBytes<32>),
+    Secp256k1(FixedBytes<33>),
+    Secp256r1(FixedBytes<33>),
+}
+
+impl WirePublicKey {
+    fn new(scheme: KeyScheme, public_key: &[u8]) -> NautilusResult<Self> {
+        let wrong_length = |_| {
+            invalid(format!(
+                "{scheme:?} public key of {} bytes",
+                public_key.len()
+            ))
+        };
+        Ok(match scheme {
+            KeyScheme::Ed25519 => {
+                WirePublicKey::Ed25519(FixedBytes::new(public_key).map_err(wrong_length)?)
+            }
+            KeyScheme::Secp256k1 => {
+                WirePublicKey::Secp256k1(FixedBytes::new(public_key).map_err(wrong_length)?)
+            }
+            KeyScheme::Secp256r1 => {
+                WirePublicKey::Secp256r1(FixedBytes::new(public_key).map_err(wrong_length)?)
+            }
+            KeyScheme::Bls12381 => {
+                return Err(invalid("BLS12-381 keys cannot join a Sui multisig"))
+            }
+        })
+    }
+
+    fn into_parts(self) -> (KeyScheme, Vec<u8>) {
+        match self {
+            WirePublicKey::Ed25519(pk) => (KeyScheme::Ed25519, pk.0.to_vec()),
+            WirePublicKey::Secp256k1(pk) => (KeyScheme::Secp256k1, pk.0.to_vec()),
+            WirePublicKey::Secp256r1(pk) => (KeyScheme::Secp256r1, pk.0.to_vec()),
+        }
+    }
+}
+
+/// `CompressedSignature`: the bare 64-byte signature, without flag or public key.
+#[derive(Serialize, Deserialize)]
+enum WireSignature {
+    Ed25519(FixedBytes<64>),
+    Secp256k1(FixedBytes<64>),
+    Secp256r1(FixedBytes<64>),
+}
+
+impl WireSignature {
+    fn new(scheme: KeyScheme, signature: &[u8]) -> NautilusResult<Self> {
+        let signature = FixedBytes::new(signature)
+            .map_err(|len| NautilusError::InvalidSignature(format!("signature of {len} bytes")))?;
+        Ok(match scheme {
+            KeyScheme::Ed25519 => WireSignature::Ed25519(signature),
+            KeyScheme::Secp256k1 => WireSignature::Secp256k1(signature),
+            KeyScheme::Secp256r1 => WireSignature::Secp256r1(signature),
+            KeyScheme::Bls12381 => {
+                return Err(invalid("BLS12-381 keys cannot join a Sui multisig"))
+            }
+        })
+    }
+
+    fn parts(&self) -> (KeyScheme, &[u8]) {
+        match self {
+            WireSignature::Ed25519(sig) => (KeyScheme::Ed25519, &sig.0),
+            WireSignature::Secp256k1(sig) => (KeyScheme::Secp256k1, &sig.0),
+            WireSignature::Secp256r1(sig) => (KeyScheme::Secp256r1, &sig.0),
+        }
+    }
+}
+
+/// Fixed-size byte array encoded as a BCS tuple, i.e. without a length prefix.
+struct FixedBytes<const N: usize>([u8; N]);
+
+impl<const N: usize> FixedBytes<N> {
+    /// Fails with the actual length if `bytes` is not `N` bytes long.
+    fn new(bytes: &[u8]) -> Result<Self, usize> {
+        bytes.try_into().map(FixedBytes).map_err(|_| bytes.len())
+    }
+}
+
+impl<const N: usize> Serialize for FixedBytes<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in &self.0 {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for FixedBytes<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FixedBytesVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for FixedBytesVisitor<N> {
+            type Value = FixedBytes<N>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{N} bytes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; N];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(FixedBytes(bytes))
+            }
+        }
+
+        deserializer.deserialize_tuple(N, FixedBytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::EnclaveKeyPair;
+    use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PrivateKey};
+    use fastcrypto::encoding::{Encoding, Hex};
+    use fastcrypto::secp256k1::{Secp256k1KeyPair, Secp256k1PrivateKey};
+    use fastcrypto::secp256r1::{Secp256r1KeyPair, Secp256r1PrivateKey};
+    use fastcrypto::traits::ToFromBytes;
+
+    // Known answers computed independently (Python `cryptography` for the keys and
+    // Ed25519 signatures, with the address and BCS layout assembled by hand).
+    const ADDRESS: &str = "f5b483493921ee654c4b6c54ef165ba7fe1f7479a73831112c74815175be2131";
+    const PUBLIC_KEY: &str = "0304\
+        008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c01\
+        008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39402\
+        0102531fe6068134503d2723133227c867ac8fa6c83c537e9a44c3c5bdbdcb1fe33701\
+        020273103ec30b3ccf57daae08e93534aef144a35940cf6bbba12a0cf7cbd5d65a6401\
+        0300";
+    /// Members 0 and 1 (Ed25519, weights 1 + 2) signing [`MSG`]: flag, two
+    /// `CompressedSignature::Ed25519`, bitmap `0b11`, then the BCS public key.
+    const SIGNATURE: &str = "0302\
+        00f533f120c16a36815e20dcaa9ff129249b95637990376dfbf9eb0a43b7e4c586\
+        7fa295513247c04bf563aaa05d7624868889008e93aac279b640988125498300\
+        0019f29bb645d5a45d404afc2ec7563262a9814af4a2b4236f4ef39a1c351ce809\
+        bc03a52179379a06a8cf78a0cd7cd2e0954aaff21aaf1cec1a6fb6e7d9c7610e\
+        0300\
+        04\
+        008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c01\
+        008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39402\
+        0102531fe6068134503d2723133227c867ac8fa6c83c537e9a44c3c5bdbdcb1fe33701\
+        020273103ec30b3ccf57daae08e93534aef144a35940cf6bbba12a0cf7cbd5d65a6401\
+        0300";
+    const MSG: &[u8] = b"nautilus multisig";
+
+    /// Members: Ed25519 (weight 1), Ed25519 (2), secp256k1 (1), secp256r1 (1).
+    fn keypairs() -> Vec<EnclaveKeyPair> {
+        vec![
+            Ed25519KeyPair::from(Ed25519PrivateKey::from_bytes(&[1; 32]).unwrap()).into(),
+            Ed25519KeyPair::from(Ed25519PrivateKey::from_bytes(&[2; 32]).unwrap()).into(),
+            Secp256k1KeyPair::from(Secp256k1PrivateKey::from_bytes(&[3; 32]).unwrap()).into(),
+            Secp256r1KeyPair::from(Secp256r1PrivateKey::from_bytes(&[4; 32]).unwrap()).into(),
+        ]
+    }
+
+    fn multisig(keypairs: &[EnclaveKeyPair]) -> MultiSigPublicKey {
+        let members = keypairs
+            .iter()
+            .zip([1, 2, 1, 1])
+            .map(|(kp, weight)| MultiSigMember {
+                scheme: kp.scheme(),
+                public_key: kp.public_key_bytes(),
+                weight,
+            })
+            .collect();
+        MultiSigPublicKey::new(members, 3).unwrap()
+    }
+
+    /// `(public key, signature over MSG)` of each member.
+    fn partials(keypairs: &[EnclaveKeyPair]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        keypairs
+            .iter()
+            .map(|kp| (kp.public_key_bytes(), kp.sign(MSG)))
+            .collect()
+    }
+
+    fn combine(
+        multisig: &MultiSigPublicKey,
+        partials: &[(Vec<u8>, Vec<u8>)],
+        signers: &[usize],
+    ) -> NautilusResult<Vec<u8>> {
+        let pairs: Vec<(&[u8], &[u8])> = signers
+            .iter()
+            .map(|&i| (partials[i].0.as_slice(), partials[i].1.as_slice()))
+            .collect();
+        multisig.combine(MSG, &pairs)
+    }
+
+    #[test]
+    fn known_address_and_public_key() {
+        let multisig = multisig(&keypairs());
+        assert_eq!(Hex::encode(multisig.address()), ADDRESS);
+        assert_eq!(Hex::encode(multisig.to_bytes().unwrap()), PUBLIC_KEY);
+    }
+
+    #[test]
+    fn known_combined_signature() {
+        let keypairs = keypairs();
+        let multisig = multisig(&keypairs);
+        // Order of the partial signatures does not matter.
+        let signature = combine(&multisig, &partials(&keypairs), &[1, 0]).unwrap();
+        assert_eq!(Hex::encode(&signature), SIGNATURE);
+        multisig.verify(MSG, &signature).unwrap();
+    }
+
+    #[test]
+    fn combine_verify_round_trip() {
+        let keypairs = keypairs();
+        let multisig = multisig(&keypairs);
+        let partials = partials(&keypairs);
+        for signers in [&[1, 2][..], &[0, 2, 3], &[3, 1, 0, 2]] {
+            let signature = combine(&multisig, &partials, signers).unwrap();
+            multisig.verify(MSG, &signature).unwrap();
+            assert_eq!(verify_any(MSG, &signature).unwrap(), multisig);
+            assert!(matches!(
+                multisig.verify(b"other message", &signature),
+                Err(NautilusError::InvalidSignature(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn combine_rejects_below_threshold() {
+        let keypairs = keypairs();
+        let multisig = multisig(&keypairs);
+        assert!(matches!(
+            combine(&multisig, &partials(&keypairs), &[0, 2]),
+            Err(NautilusError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn combine_rejects_duplicate_signer() {
+        let keypairs = keypairs();
+        let multisig = multisig(&keypairs);
+        assert!(matches!(
+            combine(&multisig, &partials(&keypairs), &[1, 1]),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn combine_rejects_non_member() {
+        let keypairs = keypairs();
+        let multisig = multisig(&keypairs[..3]);
+        let multisig = MultiSigPublicKey::new(multisig.members, 2).unwrap();
+        assert!(matches!(
+            combine(&multisig, &partials(&keypairs), &[1, 3]),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_other_multisig() {
+        let keypairs = keypairs();
+        let signature = combine(&multisig(&keypairs), &partials(&keypairs), &[1, 2]).unwrap();
+        let other = MultiSigPublicKey::new(multisig(&keypairs).members, 2).unwrap();
+        assert!(matches!(
+            other.verify(MSG, &signature),
+            Err(NautilusError::InvalidSignature(_))
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Access to the Nitro Secure Module.
+//!
+//! Every NSM request in the crate goes through [`process_request`], which talks
+//! to the `/dev/nsm` driver, or to the simulated NSM in [`crate::mock_nsm`] when
+//! the `mock-nsm` feature is enabled.
+//!
+//! The driver is accessed through one shared [`NsmSession`]: the device is
+//! opened on first use, requests from all threads are serialized on it, a
+//! driver failure drops the handle so the next request reconnects, and
+//! [`close_session`] tears it down at shutdown.
+use crate::error::{NautilusError, NautilusResult};
+use nsm_api::api::{ErrorCode, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::sync::{Mutex, PoisonError};
+
+/// Send `request` to the NSM. An NSM `Error` response is returned as `NsmRequestFailed`.
+pub fn process_request(request: Request) -> NautilusResult<Response> {
+    match dispatch(request)? {
+        Response::Error(code) => Err(NautilusError::NsmRequestFailed(format!("{code:?}"))),
+        response => Ok(response),
+    }
+}
+
+#[cfg(not(feature = "mock-nsm"))]
+fn dispatch(request: Request) -> NautilusResult<Response> {
+    SESSION.process(request)
+}
+
+#[cfg(feature = "mock-nsm")]
+fn dispatch(request: Request) -> NautilusResult<Response> {
+    crate::mock_nsm::process_request(request)
+}
+
+/// Thread-safe, lazily opened handle to the NSM device.
+pub struct NsmSession {
+    fd: Mutex<Option<i32>>,
+}
+
+static SESSION: NsmSession = NsmSession::new();
+
+impl NsmSession {
+    pub const fn new() -> Self {
+        NsmSession {
+            fd: Mutex::new(None),
+        }
+    }
+
+    /// Send `request` on this session, opening the device if needed.
+    /// If the driver reports an internal error, the handle is closed so the next
+    /// request reopens the device.
+    pub fn process(&self, request: Request) -> NautilusResult<Response> {
+        let mut fd = self.fd.lock().unwrap_or_else(PoisonError::into_inner);
+        let current = match *fd {
+            Some(current) => current,
+            None => {
+                let opened = nsm_api::driver::nsm_init();
+                if opened < 0 {
+                    return Err(NautilusError::NsmUnavailable);
+                }
+                *fd = Some(opened);
+                opened
+            }
+        };
+        let response = nsm_api::driver::nsm_process_request(current, request);
+        if matches!(response, Response::Error(ErrorCode::InternalError)) {
+            nsm_api::driver::nsm_exit(current);
+            *fd = None;
+        }
+        Ok(response)
+    }
+
+    /// Close the device handle, if open. A later request reopens it.
+    pub fn close(&self) {
+        let mut fd = self.fd.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(current) = fd.take() {
+            nsm_api::driver::nsm_exit(current);
+        }
+    }
+}
+
+impl Default for NsmSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Close the shared NSM session, e.g. at process shutdown.
+pub fn close_session() {
+    SESSION.close();
+}
+
+/// Error for a response that does not match the request kind.
+pub(crate) fn unexpected_response(request: &str) -> NautilusError {
+    NautilusError::NsmRequestFailed(format!("unexpected response to {request} request"))
+}
+
+/// Request an attestation document from the NSM committing to `public_key`,
+/// and optionally `user_data` and `nonce`.
+pub fn request_attestation(
+    public_key: &[u8],
+    user_data: Option<ByteBuf>,
+    nonce: Option<ByteBuf>,
+) -> NautilusResult<Vec<u8>> {
+    let request = Request::Attestation {
+        user_data,
+        nonce,
+        public_key: Some(ByteBuf::from(public_key.to_vec())),
+    };
+    match process_request(request)? {
+        Response::Attestation { document } => Ok(document),
+        _ => Err(unexpected_response("Attestation")),
+    }
+}
+
+/// Module information as reported by `DescribeNSM`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NsmDescription {
+    /// Module version as `major.minor.patch`.
+    pub version: String,
+    pub module_id: String,
+    pub max_pcrs: u16,
+    pub locked_pcrs: Vec<u16>,
+    /// Digest algorithm used for PCRs, e.g. `SHA384`.
+    pub digest: String,
+}
+
+/// Read the NSM module version, ID, PCR count, locked PCRs and digest algorithm.
+pub fn describe_nsm() -> NautilusResult<NsmDescription> {
+    match process_request(Request::DescribeNSM)? {
+        Response::DescribeNSM {
+            version_major,
+            version_minor,
+            version_patch,
+            module_id,
+            max_pcrs,
+            locked_pcrs,
+            digest,
+        } => Ok(NsmDescription {
+            version: format!("{version_major}.{version_minor}.{version_patch}"),
+            module_id,
+            max_pcrs,
+            locked_pcrs: locked_pcrs.into_iter().collect(),
+            digest: format!("{digest:?}"),
+        }),
+        _ => Err(unexpected_response("DescribeNSM")),
+    }
+}
+
+/// State of a PCR as reported by `DescribePCR`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PcrDescription {
+    pub locked: bool,
+    pub value: Vec<u8>,
+}
+
+/// Read the current value and lock state of PCR `index`.
+pub fn describe_pcr(index: u16) -> NautilusResult<PcrDescription> {
+    match process_request(Request::DescribePCR { index })? {
+        Response::DescribePCR { lock, data } => Ok(PcrDescription {
+            locked: lock,
+            value: data,
+        }),
+        _ => Err(unexpected_response("DescribePCR")),
+    }
+}
+
+/// Extend PCR `index` with `data` (`PCR = SHA384(PCR || data)`) and return the new value.
+/// Only unlocked PCRs can be extended; PCR16 and above are free for applications.
+pub fn extend_pcr(index: u16, data: &[u8]) -> NautilusResult<Vec<u8>> {
+    let request = Request::ExtendPCR {
+        index,
+        data: data.to_vec(),
+    };
+    match process_request(request)? {
+        Response::ExtendPCR { data } => Ok(data),
+        _ => Err(unexpected_response("ExtendPCR")),
+    }
+}
+
+/// Lock PCR `index` so it can no longer be extended.
+/// Locked PCRs are included in subsequent attestation documents.
+pub fn lock_pcr(index: u16) -> NautilusResult<()> {
+    match process_request(Request::LockPCR { index })? {
+        Response::LockPCR => Ok(()),
+        _ => Err(unexpected_response("LockPCR")),
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Randomness for key generation.
+//!
+//! Keys are generated from a CSPRNG seeded with entropy from the NSM `GetRandom`
+//! request, XOR-mixed with the OS RNG by default, so a freshly booted enclave
+//! with a poorly seeded kernel pool still gets strong keys. What happens when the
+//! NSM is unavailable is set by an explicit [`RngPolicy`], and the source actually
+//! used for the last key is reported by [`last_entropy_source`].
+//!
+//! Seeds are zeroized after use, and the generator state is wiped when the
+//! [`KeyRng`] is dropped.
+use crate::error::{NautilusError, NautilusResult};
+use crate::nsm;
+use nsm_api::api::{Request, Response};
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU8, Ordering};
+use zeroize::Zeroize;
+
+/// Which entropy sources seed key generation, and whether falling back to the OS RNG is allowed.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngPolicy {
+    /// NSM entropy mixed with the OS RNG; fail if the NSM is unavailable (default).
+    NsmMixed = 0,
+    /// NSM entropy only; fail if the NSM is unavailable.
+    NsmOnly = 1,
+    /// NSM entropy mixed with the OS RNG; fall back to the OS RNG alone if the NSM is
+    /// unavailable. The fallback is reported as [`EntropySource::OsFallback`].
+    NsmMixedOrOs = 2,
+    /// OS RNG only, e.g. for development outside an enclave.
+    Os = 3,
+}
+
+impl TryFrom<u8> for RngPolicy {
+    type Error = NautilusError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RngPolicy::NsmMixed),
+            1 => Ok(RngPolicy::NsmOnly),
+            2 => Ok(RngPolicy::NsmMixedOrOs),
+            3 => Ok(RngPolicy::Os),
+            other => Err(NautilusError::InvalidArgument(format!(
+                "unknown RNG policy: {other}"
+            ))),
+        }
+    }
+}
+
+/// Entropy actually used to seed the last generated key.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntropySource {
+    /// No key has been generated yet.
+    None = 0,
+    NsmMixed = 1,
+    Nsm = 2,
+    /// OS RNG because the NSM was unavailable under [`RngPolicy::NsmMixedOrOs`].
+    OsFallback = 3,
+    /// OS RNG as requested by [`RngPolicy::Os`].
+    Os = 4,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(RngPolicy::NsmMixed as u8);
+static LAST_SOURCE: AtomicU8 = AtomicU8::new(EntropySource::None as u8);
+
+pub fn set_policy(policy: RngPolicy) {
+    POLICY.store(policy as u8, Ordering::SeqCst);
+}
+
+pub fn policy() -> RngPolicy {
+    RngPolicy::try_from(POLICY.load(Ordering::SeqCst)).unwrap_or(RngPolicy::NsmMixed)
+}
+
+pub fn last_entropy_source() -> EntropySource {
+    match LAST_SOURCE.load(Ordering::SeqCst) {
+        1 => EntropySource::NsmMixed,
+        2 => EntropySource::Nsm,
+        3 => EntropySource::OsFallback,
+        4 => EntropySource::Os,
+        _ => EntropySource::None,
+    }
+}
+
+/// Fill `buf` with entropy from NSM `GetRandom` requests.
+pub fn nsm_fill_bytes(buf: &mut [u8]) -> NautilusResult<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let random = match nsm::process_request(Request::GetRandom)? {
+            Response::GetRandom { random } if !random.is_empty() => random,
+            _ => return Err(nsm::unexpected_response("GetRandom")),
+        };
+        let n = random.len().min(buf.len() - filled);
+        buf[filled..filled + n].copy_from_slice(&random[..n]);
+        filled += n;
+    }
+    Ok(())
+}
+
+/// CSPRNG for key generation, whose state is wiped on drop.
+pub struct KeyRng(StdRng);
+
+impl Deref for KeyRng {
+    type Target = StdRng;
+
+    fn deref(&self) -> &StdRng {
+        &self.0
+    }
+}
+
+impl DerefMut for KeyRng {
+    fn deref_mut(&mut self) -> &mut StdRng {
+        &mut self.0
+    }
+}
+
+impl Drop for KeyRng {
+    fn drop(&mut self) {
+        // SAFETY: `StdRng` is plain data (cipher state and output buffer) without
+        // pointers or a destructor, and is never used after being zeroed.
+        unsafe { zeroize::zeroize_flat_type(&mut self.0) };
+    }
+}
+
+/// Create a CSPRNG for key generation according to the current [`RngPolicy`],
+/// recording the entropy source used.
+pub fn key_rng() -> NautilusResult<KeyRng> {
+    let mut seed = [0u8; 32];
+    let source = match policy() {
+        RngPolicy::Os => EntropySource::Os,
+        RngPolicy::NsmOnly => {
+            nsm_fill_bytes(&mut seed)?;
+            EntropySource::Nsm
+        }
+        RngPolicy::NsmMixed => {
+            nsm_fill_bytes(&mut seed)?;
+            EntropySource::NsmMixed
+        }
+        RngPolicy::NsmMixedOrOs => match nsm_fill_bytes(&mut seed) {
+            Ok(()) => EntropySource::NsmMixed,
+            Err(NautilusError::NsmUnavailable) => EntropySource::OsFallback,
+            Err(e) => return Err(e),
+        },
+    };
+    if source != EntropySource::Nsm {
+        let mut os = [0u8; 32];
+        OsRng.fill_bytes(&mut os);
+        seed.iter_mut().zip(os).for_each(|(s, o)| *s ^= o);
+        os.zeroize();
+    }
+    LAST_SOURCE.store(source as u8, Ordering::SeqCst);
+    let rng = KeyRng(StdRng::from_seed(seed));
+    seed.zeroize();
+    Ok(rng)
+}
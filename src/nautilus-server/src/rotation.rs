@@ -0,0 +1,192 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key rotation with a signed handover statement.
+//!
+//! Rotating replaces the enclave key with a fresh one of the same scheme. The old
+//! key signs a [`KeyHandover`] naming both public keys and the reason, wrapped in
+//! an `IntentMessage` under [`IntentScope::KeyRotation`] so it can never be
+//! mistaken for application data. The new key is attested with the Blake2b-256
+//! digest of that signed message as `user_data`, tying the handover to the enclave.
+//!
+//! Consumers that trust the old key follow the chain with [`verify_handover`]. In
+//! Move, `enclave::verify_signature` checks the same bytes for a struct declared
+//! with the fields of [`KeyHandover`] in order and intent scope `1`.
+use crate::error::{NautilusError, NautilusResult};
+use crate::keys::{self, EnclaveKeyPair, KeyScheme};
+use crate::{IntentMessage, IntentScope};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::{Deserialize, Serialize};
+
+/// Statement by the old key that `new_public_key` replaces it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyHandover {
+    pub old_public_key: Vec<u8>,
+    pub new_public_key: Vec<u8>,
+    /// Free-form reason, e.g. `"scheduled"` or `"compromise suspected"`.
+    pub reason: String,
+}
+
+/// A handover intent message, its BCS encoding and the old key's signature over it.
+#[derive(Clone, Debug)]
+pub struct SignedHandover {
+    pub message: IntentMessage<KeyHandover>,
+    pub bcs: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl SignedHandover {
+    /// Blake2b-256 of the BCS message, committed to as `user_data` in the new key's attestation.
+    pub fn digest(&self) -> [u8; 32] {
+        Blake2b256::digest(&self.bcs).digest
+    }
+}
+
+/// Sign the handover from `old` to `new_public_key` at `timestamp_ms`.
+pub fn sign_handover(
+    old: &EnclaveKeyPair,
+    new_public_key: &[u8],
+    timestamp_ms: u64,
+    reason: &str,
+) -> NautilusResult<SignedHandover> {
+    let message = IntentMessage {
+        intent: IntentScope::KeyRotation,
+        timestamp_ms,
+        data: KeyHandover {
+            old_public_key: old.public_key_bytes(),
+            new_public_key: new_public_key.to_vec(),
+            reason: reason.to_string(),
+        },
+    };
+    let bcs = bcs::to_bytes(&message).map_err(|e| NautilusError::Serialization(e.to_string()))?;
+    let signature = old.sign(&bcs);
+    Ok(SignedHandover {
+        message,
+        bcs,
+        signature,
+    })
+}
+
+/// Check that `handover_bcs` is a key rotation intent message signed by `old_public_key`
+/// (of the given `scheme`) about that key, and return the handover with its timestamp.
+pub fn verify_handover(
+    scheme: KeyScheme,
+    old_public_key: &[u8],
+    handover_bcs: &[u8],
+    signature: &[u8],
+) -> NautilusResult<IntentMessage<KeyHandover>> {
+    keys::verify(scheme, old_public_key, handover_bcs, signature)?;
+    let message: IntentMessage<KeyHandover> = bcs::from_bytes(handover_bcs)
+        .map_err(|e| NautilusError::InvalidArgument(format!("malformed handover: {e}")))?;
+    if !matches!(message.intent, IntentScope::KeyRotation) {
+        return Err(NautilusError::InvalidArgument(
+            "not a key rotation intent message".to_string(),
+        ));
+    }
+    if message.data.old_public_key != old_public_key {
+        return Err(NautilusError::InvalidArgument(
+            "handover is for a different key".to_string(),
+        ));
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const TIMESTAMP_MS: u64 = 1744038900000;
+
+    fn keypair(seed: u64) -> EnclaveKeyPair {
+        EnclaveKeyPair::generate(KeyScheme::Ed25519, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn handover() -> (EnclaveKeyPair, SignedHandover) {
+        let (old, new) = (keypair(1), keypair(2));
+        let signed =
+            sign_handover(&old, &new.public_key_bytes(), TIMESTAMP_MS, "scheduled").unwrap();
+        (old, signed)
+    }
+
+    fn verify(
+        old: &EnclaveKeyPair,
+        bcs: &[u8],
+        signature: &[u8],
+    ) -> NautilusResult<IntentMessage<KeyHandover>> {
+        verify_handover(KeyScheme::Ed25519, &old.public_key_bytes(), bcs, signature)
+    }
+
+    #[test]
+    fn handover_round_trip() {
+        let (old, signed) = handover();
+        let message = verify(&old, &signed.bcs, &signed.signature).unwrap();
+        assert!(matches!(message.intent, IntentScope::KeyRotation));
+        assert_eq!(message.timestamp_ms, TIMESTAMP_MS);
+        assert_eq!(message.data, signed.message.data);
+        assert_eq!(message.data.new_public_key, keypair(2).public_key_bytes());
+    }
+
+    #[test]
+    fn rejects_tampered_new_key() {
+        let (old, signed) = handover();
+        let mut message = signed.message.clone();
+        message.data.new_public_key = keypair(3).public_key_bytes();
+        let tampered = bcs::to_bytes(&message).unwrap();
+        assert!(matches!(
+            verify(&old, &tampered, &signed.signature),
+            Err(NautilusError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_wrong_old_key() {
+        let (_, signed) = handover();
+        assert!(matches!(
+            verify(&keypair(3), &signed.bcs, &signed.signature),
+            Err(NautilusError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_replay_with_other_timestamp_or_intent() {
+        let (old, signed) = handover();
+        let mut later = signed.message.clone();
+        later.timestamp_ms += 1;
+        let mut process_data = signed.message.clone();
+        process_data.intent = IntentScope::ProcessData;
+        for replayed in [later, process_data] {
+            let bcs = bcs::to_bytes(&replayed).unwrap();
+            assert!(matches!(
+                verify(&old, &bcs, &signed.signature),
+                Err(NautilusError::InvalidSignature(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_signed_message_of_another_intent() {
+        let (old, signed) = handover();
+        let mut process_data = signed.message.clone();
+        process_data.intent = IntentScope::ProcessData;
+        let bcs = bcs::to_bytes(&process_data).unwrap();
+        assert!(matches!(
+            verify(&old, &bcs, &old.sign(&bcs)),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_handover_for_another_key() {
+        let (old, other) = (keypair(1), keypair(3));
+        let signed =
+            sign_handover(&other, &keypair(2).public_key_bytes(), TIMESTAMP_MS, "").unwrap();
+        let bcs = signed.bcs.clone();
+        // Signed by `old`, but naming `other` as the key being replaced.
+        assert!(matches!(
+            verify(&old, &bcs, &old.sign(&bcs)),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+}
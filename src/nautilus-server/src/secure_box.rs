@@ -0,0 +1,283 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Heap storage for private key material.
+//!
+//! A [`SecureBox`] keeps its value at a fixed heap address, `mlock`s the pages
+//! holding it so they are never written to swap, and on drop runs the value's own
+//! destructor (fastcrypto private keys zeroize themselves) and then overwrites
+//! every byte of the allocation before releasing it.
+//!
+//! Locking is best effort: it is skipped on non-Unix platforms and its failures
+//! (e.g. an exhausted `RLIMIT_MEMLOCK`) are ignored, since Nitro Enclaves have no
+//! swap anyway. Pages can hold several boxes, so locks are counted per page and a
+//! page is unlocked only when the last box on it is dropped.
+//!
+//! A value is built elsewhere and then moved in, e.g. a keypair returned on the
+//! stack by fastcrypto key generation. [`SecureBox::new`] copies it to the heap
+//! and wipes the argument it was passed, but copies made before that, in callers'
+//! frames or inside the libraries, are neither locked nor wiped.
+use std::collections::BTreeMap;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::Deref;
+use std::ptr;
+use std::sync::{Mutex, PoisonError};
+use zeroize::Zeroize;
+
+/// A heap value locked into RAM where possible and wiped on drop.
+pub struct SecureBox<T> {
+    inner: Box<ManuallyDrop<T>>,
+}
+
+impl<T> SecureBox<T> {
+    pub fn new(value: T) -> Self {
+        let mut source = MaybeUninit::new(value);
+        let secure = SecureBox {
+            inner: move_to_heap(&mut source),
+        };
+        lock_pages(secure.address(), std::mem::size_of::<T>());
+        secure
+    }
+
+    fn address(&self) -> usize {
+        &**self.inner as *const T as usize
+    }
+}
+
+impl<T> Deref for SecureBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Drop for SecureBox<T> {
+    fn drop(&mut self) {
+        let (address, len) = (self.address(), std::mem::size_of::<T>());
+        // SAFETY: the value is dropped exactly once, here; the box then frees the
+        // allocation without dropping it again, and the wiped bytes are never read.
+        unsafe { drop_and_wipe(&mut self.inner) };
+        unlock_pages(address, len);
+    }
+}
+
+/// Move the value out of `source` into a new heap allocation, then wipe `source`.
+fn move_to_heap<T>(source: &mut MaybeUninit<T>) -> Box<ManuallyDrop<T>> {
+    let mut inner = Box::<ManuallyDrop<T>>::new_uninit();
+    // SAFETY: `source` is initialized and the copy becomes its only owner; the
+    // wiped `source` is never read as a `T` again.
+    unsafe {
+        ptr::copy_nonoverlapping(source.as_ptr(), inner.as_mut_ptr().cast::<T>(), 1);
+        wipe(source.as_mut_ptr());
+        inner.assume_init()
+    }
+}
+
+/// Run the destructor of `value`, then overwrite its bytes with zeros.
+///
+/// # Safety
+/// `value` must not be used (or dropped) again.
+unsafe fn drop_and_wipe<T>(value: &mut ManuallyDrop<T>) {
+    ManuallyDrop::drop(value);
+    wipe::<T>(&mut **value);
+}
+
+/// Overwrite the `size_of::<T>()` bytes at `value` with zeros.
+///
+/// # Safety
+/// `value` must be valid for writes and must not be read as a `T` afterwards.
+unsafe fn wipe<T>(value: *mut T) {
+    std::slice::from_raw_parts_mut(value.cast::<MaybeUninit<u8>>(), std::mem::size_of::<T>())
+        .zeroize();
+}
+
+/// Number of live boxes on each locked page, by page address.
+static LOCKED_PAGES: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+/// Addresses of the pages spanned by `len > 0` bytes at `address`.
+fn pages(address: usize, len: usize) -> impl Iterator<Item = usize> {
+    let page_size = page_size();
+    let first = address / page_size * page_size;
+    let last = (address + len - 1) / page_size * page_size;
+    (first..=last).step_by(page_size)
+}
+
+fn lock_pages(address: usize, len: usize) {
+    let mut locked = LOCKED_PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+    for page in count_locks(&mut locked, address, len) {
+        os::mlock(page, page_size());
+    }
+}
+
+fn unlock_pages(address: usize, len: usize) {
+    let mut locked = LOCKED_PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+    for page in count_unlocks(&mut locked, address, len) {
+        os::munlock(page, page_size());
+    }
+}
+
+/// Count one more box on each page spanned by `len` bytes at `address`, returning
+/// the pages that had none and so need locking.
+fn count_locks(locked: &mut BTreeMap<usize, usize>, address: usize, len: usize) -> Vec<usize> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut newly_locked = Vec::new();
+    for page in pages(address, len) {
+        let count = locked.entry(page).or_insert(0);
+        if *count == 0 {
+            newly_locked.push(page);
+        }
+        *count += 1;
+    }
+    newly_locked
+}
+
+/// Count one box fewer on each page spanned by `len` bytes at `address`, returning
+/// the pages left with none and so need unlocking.
+fn count_unlocks(locked: &mut BTreeMap<usize, usize>, address: usize, len: usize) -> Vec<usize> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut unlocked = Vec::new();
+    for page in pages(address, len) {
+        if let Some(count) = locked.get_mut(&page) {
+            *count -= 1;
+            if *count == 0 {
+                locked.remove(&page);
+                unlocked.push(page);
+            }
+        }
+    }
+    unlocked
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions.
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(unix)]
+mod os {
+    // SAFETY (both): the range covers mapped pages of a live allocation; failures
+    // leave memory unchanged and are deliberately ignored.
+    pub fn mlock(page: usize, len: usize) {
+        unsafe { libc::mlock(page as *const libc::c_void, len) };
+    }
+
+    pub fn munlock(page: usize, len: usize) {
+        unsafe { libc::munlock(page as *const libc::c_void, len) };
+    }
+}
+
+#[cfg(not(unix))]
+mod os {
+    pub fn mlock(_page: usize, _len: usize) {}
+
+    pub fn munlock(_page: usize, _len: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A value whose destructor records that it ran.
+    struct Secret<'a> {
+        bytes: [u8; 48],
+        dropped: &'a Cell<bool>,
+    }
+
+    impl Drop for Secret<'_> {
+        fn drop(&mut self) {
+            self.dropped.set(true);
+        }
+    }
+
+    fn bytes_of<T>(value: *const T) -> Vec<u8> {
+        // SAFETY: test values are fully initialized or zeroed and have no padding.
+        unsafe { std::slice::from_raw_parts(value.cast::<u8>(), std::mem::size_of::<T>()) }.to_vec()
+    }
+
+    #[test]
+    fn new_wipes_the_moved_from_value() {
+        let mut source = MaybeUninit::new([0xa5u8; 64]);
+        let inner = move_to_heap(&mut source);
+        assert_eq!(**inner, [0xa5; 64]);
+        assert_eq!(bytes_of(source.as_ptr()), vec![0; 64]);
+    }
+
+    #[test]
+    fn drop_runs_destructor_then_wipes() {
+        let dropped = Cell::new(false);
+        let mut secret = ManuallyDrop::new(Secret {
+            bytes: [0x5a; 48],
+            dropped: &dropped,
+        });
+        assert_eq!(secret.bytes, [0x5a; 48]);
+        unsafe { drop_and_wipe(&mut secret) };
+        assert!(dropped.get());
+        assert!(bytes_of(ptr::addr_of!(secret)).iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn secure_box_derefs_and_drops_value() {
+        let dropped = Cell::new(false);
+        let secret = SecureBox::new(Secret {
+            bytes: [7; 48],
+            dropped: &dropped,
+        });
+        assert_eq!(secret.bytes, [7; 48]);
+        drop(secret);
+        assert!(dropped.get());
+    }
+
+    #[test]
+    fn pages_are_locked_once_and_unlocked_by_the_last_box() {
+        let page = page_size();
+        let mut locked = BTreeMap::new();
+
+        assert_eq!(count_locks(&mut locked, 3 * page + 8, 32), vec![3 * page]);
+        assert!(count_locks(&mut locked, 3 * page + 64, 32).is_empty());
+        assert_eq!(locked[&(3 * page)], 2);
+
+        assert!(count_unlocks(&mut locked, 3 * page + 8, 32).is_empty());
+        assert_eq!(
+            count_unlocks(&mut locked, 3 * page + 64, 32),
+            vec![3 * page]
+        );
+        assert!(locked.is_empty());
+    }
+
+    #[test]
+    fn boxes_spanning_pages_count_on_each_page() {
+        let page = page_size();
+        let mut locked = BTreeMap::new();
+
+        assert_eq!(
+            count_locks(&mut locked, 5 * page - 16, 32),
+            vec![4 * page, 5 * page]
+        );
+        assert!(count_locks(&mut locked, 5 * page + 16, 32).is_empty());
+        assert_eq!(
+            count_unlocks(&mut locked, 5 * page - 16, 32),
+            vec![4 * page]
+        );
+        assert_eq!(
+            count_unlocks(&mut locked, 5 * page + 16, 32),
+            vec![5 * page]
+        );
+        assert!(locked.is_empty());
+        assert!(count_locks(&mut locked, page, 0).is_empty());
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline construction of the Sui transaction registering an enclave.
+//!
+//! Produces the BCS `TransactionData` of the programmable transaction built by
+//! `register_enclave.sh`:
+//! 1. `0x2::nitro_attestation::load_nitro_attestation(document, &Clock)`;
+//! 2. `<enclave package>::enclave::register_enclave<T>(&EnclaveConfig<T>, result 0)`.
+//!
+//! The bytes are ready to be signed (e.g. `sui keytool sign --data <base64>`) and
+//! submitted elsewhere; nothing here talks to a fullnode, so object versions and
+//! gas coins must be supplied by the caller. The wire types mirror `sui-types`.
+use crate::error::{NautilusError, NautilusResult};
+use fastcrypto::encoding::{Base58, Encoding, Hex};
+use serde::Serialize;
+
+/// Sui address or object ID.
+pub type Address = [u8; 32];
+
+/// The shared `0x6` Clock object, created at genesis.
+pub const CLOCK_OBJECT_ID: Address = address_from_u8(6);
+const CLOCK_INITIAL_SHARED_VERSION: u64 = 1;
+
+/// The Sui framework package (`0x2`).
+pub const SUI_FRAMEWORK_ID: Address = address_from_u8(2);
+
+const fn address_from_u8(last: u8) -> Address {
+    let mut address = [0u8; 32];
+    address[31] = last;
+    address
+}
+
+/// Parse a hex address, accepting the short form (`0x6`).
+pub fn parse_address(s: &str) -> NautilusResult<Address> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    let invalid = || NautilusError::InvalidArgument(format!("invalid Sui address: {s}"));
+    if hex.is_empty() || hex.len() > 64 {
+        return Err(invalid());
+    }
+    let bytes = Hex::decode(&format!("{hex:0>64}")).map_err(|_| invalid())?;
+    bytes.try_into().map_err(|_| invalid())
+}
+
+/// Parse a base58 object digest, as printed by the Sui CLI.
+pub fn parse_digest(s: &str) -> NautilusResult<[u8; 32]> {
+    let invalid = || NautilusError::InvalidArgument(format!("invalid object digest: {s}"));
+    Base58::decode(s)
+        .map_err(|_| invalid())?
+        .try_into()
+        .map_err(|_| invalid())
+}
+
+/// Reference to an owned object at a given version, e.g. a gas coin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub object_id: Address,
+    pub version: u64,
+    pub digest: [u8; 32],
+}
+
+/// Reference to a shared object, e.g. an `EnclaveConfig<T>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedObjectRef {
+    pub object_id: Address,
+    pub initial_shared_version: u64,
+}
+
+/// Everything needed to build the registration transaction.
+#[derive(Clone, Debug)]
+pub struct RegisterEnclaveTx {
+    /// Raw attestation document from the enclave.
+    pub attestation: Vec<u8>,
+    /// Package defining the `enclave` module.
+    pub enclave_package_id: Address,
+    pub enclave_config: SharedObjectRef,
+    /// Package, module and one-time witness name of the application type `T`.
+    pub app_package_id: Address,
+    pub module_name: String,
+    pub otw_name: String,
+    pub sender: Address,
+    pub gas_payment: Vec<ObjectRef>,
+    pub gas_price: u64,
+    pub gas_budget: u64,
+}
+
+// BCS wire types, declared in the same order (and so with the same variant
+// indices) as their `sui-types` and `move-core-types` counterparts.
+
+#[derive(Serialize)]
+enum TransactionData {
+    V1(TransactionDataV1),
+}
+
+#[derive(Serialize)]
+struct TransactionDataV1 {
+    kind: TransactionKind,
+    sender: Address,
+    gas_data: GasData,
+    expiration: TransactionExpiration,
+}
+
+#[derive(Serialize)]
+enum TransactionKind {
+    ProgrammableTransaction(ProgrammableTransaction),
+}
+
+#[derive(Serialize)]
+struct ProgrammableTransaction {
+    inputs: Vec<CallArg>,
+    commands: Vec<Command>,
+}
+
+#[derive(Serialize)]
+enum CallArg {
+    Pure(Vec<u8>),
+    Object(ObjectArg),
+}
+
+#[allow(dead_code)]
+#[derive(Serialize)]
+enum ObjectArg {
+    ImmOrOwnedObject(WireObjectRef),
+    SharedObject {
+        id: Address,
+        initial_shared_version: u64,
+        mutable: bool,
+    },
+}
+
+/// `(ObjectID, SequenceNumber, ObjectDigest)`; digests are BCS byte vectors.
+#[derive(Serialize)]
+struct WireObjectRef(Address, u64, serde_bytes::ByteBuf);
+
+#[derive(Serialize)]
+enum Command {
+    MoveCall(ProgrammableMoveCall),
+}
+
+#[derive(Serialize)]
+struct ProgrammableMoveCall {
+    package: Address,
+    module: String,
+    function: String,
+    type_arguments: Vec<TypeTag>,
+    arguments: Vec<Argument>,
+}
+
+#[allow(dead_code)]
+#[derive(Serialize)]
+enum TypeTag {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(StructTag),
+}
+
+#[derive(Serialize)]
+struct StructTag {
+    address: Address,
+    module: String,
+    name: String,
+    type_params: Vec<TypeTag>,
+}
+
+#[allow(dead_code)]
+#[derive(Serialize)]
+enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+}
+
+#[derive(Serialize)]
+struct GasData {
+    payment: Vec<WireObjectRef>,
+    owner: Address,
+    price: u64,
+    budget: u64,
+}
+
+#[derive(Serialize)]
+enum TransactionExpiration {
+    None,
+}
+
+fn to_bcs<T: Serialize>(value: &T) -> NautilusResult<Vec<u8>> {
+    bcs::to_bytes(value).map_err(|e| NautilusError::Serialization(e.to_string()))
+}
+
+impl RegisterEnclaveTx {
+    /// Serialize the transaction as BCS `TransactionData`.
+    pub fn to_bytes(&self) -> NautilusResult<Vec<u8>> {
+        if self.gas_payment.is_empty() {
+            return Err(NautilusError::InvalidArgument(
+                "at least one gas coin is required".to_string(),
+            ));
+        }
+        let inputs = vec![
+            CallArg::Pure(to_bcs(&self.attestation)?),
+            CallArg::Object(ObjectArg::SharedObject {
+                id: CLOCK_OBJECT_ID,
+                initial_shared_version: CLOCK_INITIAL_SHARED_VERSION,
+                mutable: false,
+            }),
+            CallArg::Object(ObjectArg::SharedObject {
+                id: self.enclave_config.object_id,
+                initial_shared_version: self.enclave_config.initial_shared_version,
+                mutable: false,
+            }),
+        ];
+        let commands = vec![
+            Command::MoveCall(ProgrammableMoveCall {
+                package: SUI_FRAMEWORK_ID,
+                module: "nitro_attestation".to_string(),
+                function: "load_nitro_attestation".to_string(),
+                type_arguments: vec![],
+                arguments: vec![Argument::Input(0), Argument::Input(1)],
+            }),
+            Command::MoveCall(ProgrammableMoveCall {
+                package: self.enclave_package_id,
+                module: "enclave".to_string(),
+                function: "register_enclave".to_string(),
+                type_arguments: vec![TypeTag::Struct(StructTag {
+                    address: self.app_package_id,
+                    module: self.module_name.clone(),
+                    name: self.otw_name.clone(),
+                    type_params: vec![],
+                })],
+                arguments: vec![Argument::Input(2), Argument::Result(0)],
+            }),
+        ];
+        to_bcs(&TransactionData::V1(TransactionDataV1 {
+            kind: TransactionKind::ProgrammableTransaction(ProgrammableTransaction {
+                inputs,
+                commands,
+            }),
+            sender: self.sender,
+            gas_data: GasData {
+                payment: self
+                    .gas_payment
+                    .iter()
+                    .map(|gas| {
+                        WireObjectRef(
+                            gas.object_id,
+                            gas.version,
+                            serde_bytes::ByteBuf::from(gas.digest.to_vec()),
+                        )
+                    })
+                    .collect(),
+                owner: self.sender,
+                price: self.gas_price,
+                budget: self.gas_budget,
+            },
+            expiration: TransactionExpiration::None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Golden `TransactionData`, one field per line with `#` comments.
+    const GOLDEN_TX: &str = include_str!("testdata/register_enclave_tx.hex");
+
+    fn golden_tx() -> Vec<u8> {
+        let hex: String = GOLDEN_TX
+            .lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .collect();
+        Hex::decode(&hex).unwrap()
+    }
+
+    fn register_enclave_tx() -> RegisterEnclaveTx {
+        RegisterEnclaveTx {
+            attestation: vec![0xde, 0xad, 0xbe, 0xef],
+            enclave_package_id: parse_address("0xe1").unwrap(),
+            enclave_config: SharedObjectRef {
+                object_id: parse_address("0xc0").unwrap(),
+                initial_shared_version: 42,
+            },
+            app_package_id: parse_address("0xa9").unwrap(),
+            module_name: "weather".to_string(),
+            otw_name: "WEATHER".to_string(),
+            sender: parse_address("0xa11ce").unwrap(),
+            gas_payment: vec![ObjectRef {
+                object_id: parse_address("0x9a5").unwrap(),
+                version: 7,
+                digest: [0x11; 32],
+            }],
+            gas_price: 1000,
+            gas_budget: 50_000_000,
+        }
+    }
+
+    #[test]
+    fn register_enclave_tx_matches_golden_bytes() {
+        assert_eq!(
+            Hex::encode(register_enclave_tx().to_bytes().unwrap()),
+            Hex::encode(golden_tx())
+        );
+    }
+
+    #[test]
+    fn register_enclave_tx_requires_gas() {
+        let tx = RegisterEnclaveTx {
+            gas_payment: vec![],
+            ..register_enclave_tx()
+        };
+        assert!(matches!(
+            tx.to_bytes(),
+            Err(NautilusError::InvalidArgument(_))
+        ));
+    }
+}
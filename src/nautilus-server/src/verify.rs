@@ -0,0 +1,363 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline verification of Nitro attestation documents.
+//!
+//! Mirrors the checks performed on-chain by `sui::nitro_attestation`:
+//! - the COSE_Sign1 signature (ES384) over the document, by the enclave certificate;
+//! - the certificate chain from the enclave certificate up to a trusted root;
+//! - the document timestamp against a validity window;
+//! - the PCRs against expected values.
+//!
+//! No network access is needed: the AWS Nitro root certificate is bundled
+//! (`AWS_NITRO_ROOT_CERTIFICATE_PEM`), or another root can be supplied.
+use crate::attestation::{AttestationDocument, CoseSign1};
+use crate::error::{NautilusError, NautilusResult};
+use fastcrypto::encoding::{Encoding, Hex};
+use p384::ecdsa::signature::Verifier;
+use p384::ecdsa::{Signature, VerifyingKey};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::oid_registry::OID_SIG_ECDSA_WITH_SHA384;
+use x509_parser::prelude::FromDer;
+use x509_parser::time::ASN1Time;
+
+/// AWS Nitro Enclaves root certificate (G1), as published at
+/// <https://aws-nitro-enclaves.amazonaws.com/AWS_NitroEnclaves_Root-G1.zip>.
+/// SHA-256 of the published file: `6eb9688305e4bbca67f44b59c29a0661ae930f09b5945b5d1d9ae01125c8d6c0`
+/// (the bundled copy only adds a trailing newline).
+pub const AWS_NITRO_ROOT_CERTIFICATE_PEM: &str = include_str!("nitro_root_certificate.pem");
+
+/// Default maximum age of a document at verification time.
+pub const DEFAULT_MAX_AGE_MS: u64 = 5 * 60 * 1000;
+
+/// Default tolerance for documents timestamped slightly in the future.
+pub const DEFAULT_MAX_CLOCK_SKEW_MS: u64 = 30 * 1000;
+
+/// PCR digest algorithm of Nitro attestation documents.
+const PCR_DIGEST: &str = "SHA384";
+
+/// COSE algorithm identifier for ECDSA w/ SHA-384 (RFC 8152, table 5).
+const COSE_ALG_ES384: i128 = -35;
+
+/// What a document is checked against.
+#[derive(Clone, Debug)]
+pub struct VerificationPolicy {
+    /// DER-encoded trusted root certificate.
+    pub root_certificate: Vec<u8>,
+    /// Verification time as UNIX epoch in milliseconds.
+    pub now_ms: u64,
+    /// Reject documents older than this (relative to `now_ms`).
+    pub max_age_ms: u64,
+    /// Accept documents timestamped up to this far after `now_ms`.
+    pub max_clock_skew_ms: u64,
+    /// PCR index → expected value. PCRs not listed are not checked.
+    pub expected_pcrs: BTreeMap<usize, Vec<u8>>,
+}
+
+impl VerificationPolicy {
+    /// Policy trusting the bundled AWS Nitro root, evaluated at the current system time,
+    /// with default age limits and the given expected PCRs.
+    pub fn aws_nitro(expected_pcrs: BTreeMap<usize, Vec<u8>>) -> NautilusResult<Self> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Ok(VerificationPolicy {
+            root_certificate: pem_to_der(AWS_NITRO_ROOT_CERTIFICATE_PEM)?,
+            now_ms,
+            max_age_ms: DEFAULT_MAX_AGE_MS,
+            max_clock_skew_ms: DEFAULT_MAX_CLOCK_SKEW_MS,
+            expected_pcrs,
+        })
+    }
+}
+
+/// Outcome of each check. A document is valid only if every check passed;
+/// `errors` explains each failure.
+#[derive(Serialize, Clone, Debug)]
+pub struct VerificationReport {
+    pub signature_valid: bool,
+    pub certificate_chain_valid: bool,
+    pub timestamp_valid: bool,
+    pub pcrs_match: bool,
+    /// Indices of expected PCRs that are missing or differ.
+    pub mismatched_pcrs: Vec<usize>,
+    pub errors: Vec<String>,
+    #[serde(skip)]
+    pub document: AttestationDocument,
+}
+
+impl VerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.signature_valid
+            && self.certificate_chain_valid
+            && self.timestamp_valid
+            && self.pcrs_match
+    }
+}
+
+/// Decode a PEM certificate into DER.
+pub fn pem_to_der(pem: &str) -> NautilusResult<Vec<u8>> {
+    x509_parser::pem::parse_x509_pem(pem.as_bytes())
+        .map(|(_, pem)| pem.contents)
+        .map_err(|e| NautilusError::InvalidAttestation(format!("malformed PEM certificate: {e}")))
+}
+
+/// Verify a raw attestation document against `policy`.
+///
+/// Returns an error only if the document cannot be decoded at all or does not use
+/// SHA-384 PCRs, as every Nitro document does; failed checks are reported in the
+/// returned [`VerificationReport`].
+pub fn verify_attestation(
+    document: &[u8],
+    policy: &VerificationPolicy,
+) -> NautilusResult<VerificationReport> {
+    let cose = CoseSign1::from_bytes(document)?;
+    let doc = cose.document()?;
+    if doc.digest != PCR_DIGEST {
+        return Err(NautilusError::InvalidAttestation(format!(
+            "unsupported PCR digest {}, expected {PCR_DIGEST}",
+            doc.digest
+        )));
+    }
+    let mut errors = Vec::new();
+
+    let signature_valid = record(&mut errors, verify_cose_signature(&cose, &doc.certificate));
+    let certificate_chain_valid = record(&mut errors, verify_certificate_chain(&doc, policy));
+    let timestamp_valid = record(&mut errors, verify_timestamp(doc.timestamp, policy));
+
+    let mismatched_pcrs: Vec<usize> = policy
+        .expected_pcrs
+        .iter()
+        .filter(|(index, expected)| doc.pcrs.get(index) != Some(*expected))
+        .map(|(index, _)| *index)
+        .collect();
+    for index in &mismatched_pcrs {
+        errors.push(format!(
+            "PCR{index} mismatch: expected {}, got {}",
+            Hex::encode(&policy.expected_pcrs[index]),
+            doc.pcrs.get(index).map_or("none".to_string(), Hex::encode)
+        ));
+    }
+
+    Ok(VerificationReport {
+        signature_valid,
+        certificate_chain_valid,
+        timestamp_valid,
+        pcrs_match: mismatched_pcrs.is_empty(),
+        mismatched_pcrs,
+        errors,
+        document: doc,
+    })
+}
+
+/// Push a failed check's reason into `errors` and return whether it passed.
+fn record(errors: &mut Vec<String>, check: Result<(), String>) -> bool {
+    match check {
+        Ok(()) => true,
+        Err(e) => {
+            errors.push(e);
+            false
+        }
+    }
+}
+
+/// Check the COSE_Sign1 signature over the `Sig_structure` with the key of `certificate`.
+fn verify_cose_signature(cose: &CoseSign1, certificate: &[u8]) -> Result<(), String> {
+    let header: BTreeMap<serde_cbor::Value, serde_cbor::Value> =
+        serde_cbor::from_slice(&cose.protected)
+            .map_err(|e| format!("malformed COSE protected header: {e}"))?;
+    match header.get(&serde_cbor::Value::Integer(1)) {
+        Some(serde_cbor::Value::Integer(COSE_ALG_ES384)) => {}
+        other => return Err(format!("unsupported COSE algorithm: {other:?}")),
+    }
+
+    // Sig_structure = ["Signature1", protected, external_aad, payload] (RFC 8152, section 4.4).
+    let sig_structure = serde_cbor::to_vec(&(
+        "Signature1",
+        serde_bytes::Bytes::new(&cose.protected),
+        serde_bytes::Bytes::new(&[]),
+        serde_bytes::Bytes::new(&cose.payload),
+    ))
+    .map_err(|e| format!("cannot encode COSE Sig_structure: {e}"))?;
+
+    let (_, cert) = X509Certificate::from_der(certificate)
+        .map_err(|e| format!("malformed enclave certificate: {e}"))?;
+    let signature = Signature::from_slice(&cose.signature)
+        .map_err(|e| format!("malformed COSE signature: {e}"))?;
+    verifying_key(&cert)?
+        .verify(&sig_structure, &signature)
+        .map_err(|_| "COSE signature does not verify with the enclave certificate".to_string())
+}
+
+/// Check that `cabundle` starts at the trusted root and that every certificate
+/// down to the enclave certificate is signed by its parent and currently valid.
+fn verify_certificate_chain(
+    doc: &AttestationDocument,
+    policy: &VerificationPolicy,
+) -> Result<(), String> {
+    match doc.cabundle.first() {
+        Some(root) if *root == policy.root_certificate => {}
+        _ => return Err("cabundle does not start with the trusted root certificate".to_string()),
+    }
+    let now = ASN1Time::from_timestamp((policy.now_ms / 1000) as i64)
+        .map_err(|e| format!("invalid verification time: {e}"))?;
+
+    let chain = doc
+        .cabundle
+        .iter()
+        .chain(std::iter::once(&doc.certificate))
+        .map(|der| {
+            X509Certificate::from_der(der)
+                .map(|(_, cert)| cert)
+                .map_err(|e| format!("malformed certificate in chain: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (depth, cert) in chain.iter().enumerate() {
+        if !cert.validity().is_valid_at(now) {
+            return Err(format!(
+                "certificate at depth {depth} ({}) is not valid now",
+                cert.subject()
+            ));
+        }
+    }
+    for (depth, pair) in chain.windows(2).enumerate() {
+        let (parent, child) = (&pair[0], &pair[1]);
+        if !parent.is_ca() {
+            return Err(format!(
+                "certificate at depth {depth} ({}) is not a CA",
+                parent.subject()
+            ));
+        }
+        if child.issuer() != parent.subject() {
+            return Err(format!(
+                "certificate at depth {} is issued by {}, expected {}",
+                depth + 1,
+                child.issuer(),
+                parent.subject()
+            ));
+        }
+        verify_certificate_signature(child, parent)
+            .map_err(|e| format!("certificate at depth {}: {e}", depth + 1))?;
+    }
+    Ok(())
+}
+
+/// Check `child`'s ECDSA P-384/SHA-384 signature with the key of `parent`.
+fn verify_certificate_signature(
+    child: &X509Certificate<'_>,
+    parent: &X509Certificate<'_>,
+) -> Result<(), String> {
+    if child.signature_algorithm.algorithm != OID_SIG_ECDSA_WITH_SHA384 {
+        return Err(format!(
+            "unsupported signature algorithm {}",
+            child.signature_algorithm.algorithm
+        ));
+    }
+    let signature = Signature::from_der(&child.signature_value.data)
+        .map_err(|e| format!("malformed signature: {e}"))?;
+    verifying_key(parent)?
+        .verify(child.tbs_certificate.as_ref(), &signature)
+        .map_err(|_| "signature does not verify with issuer key".to_string())
+}
+
+fn verifying_key(cert: &X509Certificate<'_>) -> Result<VerifyingKey, String> {
+    VerifyingKey::from_sec1_bytes(&cert.public_key().subject_public_key.data)
+        .map_err(|e| format!("certificate key is not a P-384 key: {e}"))
+}
+
+/// Check the document timestamp lies within `[now - max_age, now + max_clock_skew]`.
+fn verify_timestamp(timestamp_ms: u64, policy: &VerificationPolicy) -> Result<(), String> {
+    if timestamp_ms > policy.now_ms.saturating_add(policy.max_clock_skew_ms) {
+        return Err(format!(
+            "document timestamp {timestamp_ms} is in the future (now {})",
+            policy.now_ms
+        ));
+    }
+    if policy.now_ms.saturating_sub(timestamp_ms) > policy.max_age_ms {
+        return Err(format!(
+            "document timestamp {timestamp_ms} is older than {} ms (now {})",
+            policy.max_age_ms, policy.now_ms
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "mock-nsm"))]
+mod tests {
+    use super::*;
+    use crate::{mock_nsm, nsm};
+
+    fn mock_document() -> Vec<u8> {
+        nsm::request_attestation(b"public key", None, None).unwrap()
+    }
+
+    fn mock_policy(document: &[u8]) -> VerificationPolicy {
+        VerificationPolicy {
+            root_certificate: mock_nsm::root_certificate_der().unwrap(),
+            now_ms: crate::attestation::parse_attestation(document)
+                .unwrap()
+                .timestamp,
+            max_age_ms: DEFAULT_MAX_AGE_MS,
+            max_clock_skew_ms: DEFAULT_MAX_CLOCK_SKEW_MS,
+            expected_pcrs: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn valid_document() {
+        let document = mock_document();
+        let report = verify_attestation(&document, &mock_policy(&document)).unwrap();
+        assert!(report.is_valid(), "{:?}", report.errors);
+    }
+
+    #[test]
+    fn flipped_signature_byte() {
+        let mut document = mock_document();
+        let policy = mock_policy(&document);
+        // The COSE signature is the last item of the COSE_Sign1 array.
+        *document.last_mut().unwrap() ^= 1;
+        let report = verify_attestation(&document, &policy).unwrap();
+        assert!(!report.signature_valid);
+        assert!(report.certificate_chain_valid && report.timestamp_valid && report.pcrs_match);
+    }
+
+    #[test]
+    fn wrong_root() {
+        let document = mock_document();
+        let policy = VerificationPolicy {
+            root_certificate: pem_to_der(AWS_NITRO_ROOT_CERTIFICATE_PEM).unwrap(),
+            ..mock_policy(&document)
+        };
+        let report = verify_attestation(&document, &policy).unwrap();
+        assert!(!report.certificate_chain_valid);
+        assert!(report.signature_valid && report.timestamp_valid && report.pcrs_match);
+    }
+
+    #[test]
+    fn expired_window() {
+        let document = mock_document();
+        let mut policy = mock_policy(&document);
+        policy.now_ms += DEFAULT_MAX_AGE_MS + 1;
+        let report = verify_attestation(&document, &policy).unwrap();
+        assert!(!report.timestamp_valid);
+        assert!(report.signature_valid && report.certificate_chain_valid && report.pcrs_match);
+    }
+
+    #[test]
+    fn mismatched_pcr() {
+        let document = mock_document();
+        let policy = VerificationPolicy {
+            expected_pcrs: BTreeMap::from([(0, vec![0xff; 48]), (1, vec![0; 48])]),
+            ..mock_policy(&document)
+        };
+        let report = verify_attestation(&document, &policy).unwrap();
+        assert!(!report.pcrs_match);
+        assert_eq!(report.mismatched_pcrs, vec![0]);
+        assert!(report.signature_valid && report.certificate_chain_valid && report.timestamp_valid);
+    }
+}